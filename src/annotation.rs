@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+/// Reference from a trace group to a stroke of the sketch, optionally restricted to a range of its points
+///
+/// Point ranges are half-open. Operations on `Sketch` that remove points, such as simplification, shift the
+/// ranges onto the remaining points, or widen them to the whole stroke if none remain, while operations replacing the points, such as resampling, widen them to
+/// the whole stroke. Changing a stroke directly does not update the references to it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StrokeReference {
+    pub stroke: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub points: Option<(usize, usize)>,
+}
+
+/// Named node of the annotation tree of a sketch, e.g. a line, word or character
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TraceGroup {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub attributes: HashMap<String, serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub strokes: Vec<StrokeReference>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<TraceGroup>,
+}
+
+impl TraceGroup {
+    pub fn new(name: &str) -> TraceGroup {
+        TraceGroup { name: String::from(name), label: None, attributes: HashMap::new(), strokes: vec![], groups: vec![] }
+    }
+
+    pub fn with_label(name: &str, label: &str) -> TraceGroup {
+        let mut group = TraceGroup::new(name);
+        group.label = Some(String::from(label));
+        group
+    }
+
+    pub fn add_stroke(&mut self, stroke: usize) {
+        self.strokes.push(StrokeReference { stroke, points: None });
+    }
+
+    /// References the points `start..end` of a stroke
+    pub fn add_stroke_range(&mut self, stroke: usize, start: usize, end: usize) {
+        if start > end { panic!("start ({}) should be less than end ({})", start, end); }
+        self.strokes.push(StrokeReference { stroke, points: Some((start, end)) });
+    }
+
+    pub fn add_group(&mut self, group: TraceGroup) {
+        self.groups.push(group);
+    }
+
+    /// Sorted indices of all strokes referenced by this group and its descendants
+    pub fn stroke_indices(&self) -> Vec<usize> {
+        let mut indices = vec![];
+        self.collect_stroke_indices(&mut indices);
+        indices.sort_unstable();
+        indices.dedup();
+        indices
+    }
+
+    /// Depth-first search for the first group with the given name, including this group
+    pub fn find(&self, name: &str) -> Option<&TraceGroup> {
+        if self.name == name {
+            return Some(self);
+        }
+        self.groups.iter().find_map(|g| g.find(name))
+    }
+
+    fn collect_stroke_indices(&self, indices: &mut Vec<usize>) {
+        indices.extend(self.strokes.iter().map(|r| r.stroke));
+        for group in self.groups.iter() {
+            group.collect_stroke_indices(indices);
+        }
+    }
+
+    /// Updates stroke references after strokes were removed, `mapping[i]` is the new index of stroke `i`
+    pub(crate) fn remap_strokes(&mut self, mapping: &[Option<usize>]) {
+        self.remap_references(&mut |r| {
+            mapping.get(r.stroke).cloned().flatten().map(|stroke| StrokeReference { stroke, points: r.points }).into_iter().collect()
+        });
+    }
+
+    /// Replaces every reference of this group and its descendants with the references returned by `f`
+    pub(crate) fn remap_references<F>(&mut self, f: &mut F) where F: FnMut(&StrokeReference) -> Vec<StrokeReference> {
+        self.strokes = self.strokes.iter().flat_map(&mut *f).collect();
+        for group in self.groups.iter_mut() {
+            group.remap_references(f);
+        }
+    }
+}
+
+impl fmt::Display for TraceGroup {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", json!(self))
+    }
+}
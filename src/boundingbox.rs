@@ -3,6 +3,8 @@ use std::fmt;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
+use crate::transform::AffineTransform;
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct BoundingBox {
     pub x_min: f64,
@@ -64,6 +66,22 @@ impl BoundingBox {
     pub fn area(&self) -> f64 {
         self.width * self.height
     }
+
+    /// Returns the bounds of the transformed box
+    pub fn apply_transform(&self, transform: &AffineTransform) -> BoundingBox {
+        let corners = [
+            transform.apply(self.x_min, self.y_min),
+            transform.apply(self.x_min, self.y_max),
+            transform.apply(self.x_max, self.y_min),
+            transform.apply(self.x_max, self.y_max),
+        ];
+        let x_min = corners.iter().fold(f64::MAX, |a, c| a.min(c.0));
+        let y_min = corners.iter().fold(f64::MAX, |a, c| a.min(c.1));
+        let x_max = corners.iter().fold(f64::MIN, |a, c| a.max(c.0));
+        let y_max = corners.iter().fold(f64::MIN, |a, c| a.max(c.1));
+
+        BoundingBox::new(x_min, y_min, x_max, y_max)
+    }
 }
 
 impl fmt::Display for BoundingBox {
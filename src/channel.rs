@@ -0,0 +1,88 @@
+use serde::{Deserialize, Serialize};
+
+/// Additional per-point values of a stroke, e.g. tilt, azimuth, altitude, twist or button state
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "values", rename_all = "lowercase")]
+pub enum Channel {
+    Float(Vec<f64>),
+    Integer(Vec<u64>),
+    Boolean(Vec<bool>),
+}
+
+impl Channel {
+    pub fn len(&self) -> usize {
+        match self {
+            Channel::Float(values) => values.len(),
+            Channel::Integer(values) => values.len(),
+            Channel::Boolean(values) => values.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub(crate) fn remove(&mut self, index: usize) {
+        match self {
+            Channel::Float(values) => { values.remove(index); }
+            Channel::Integer(values) => { values.remove(index); }
+            Channel::Boolean(values) => { values.remove(index); }
+        }
+    }
+
+    /// Appends a copy of the last value, or the default value of the type if the channel is empty
+    pub(crate) fn repeat_last(&mut self) {
+        match self {
+            Channel::Float(values) => values.push(values.last().copied().unwrap_or_default()),
+            Channel::Integer(values) => values.push(values.last().copied().unwrap_or_default()),
+            Channel::Boolean(values) => values.push(values.last().copied().unwrap_or_default()),
+        }
+    }
+
+    pub(crate) fn split_off(&mut self, at: usize) -> Channel {
+        match self {
+            Channel::Float(values) => Channel::Float(values.split_off(at)),
+            Channel::Integer(values) => Channel::Integer(values.split_off(at)),
+            Channel::Boolean(values) => Channel::Boolean(values.split_off(at)),
+        }
+    }
+
+    /// Values at the given indices
+    pub(crate) fn select(&self, indices: &[usize]) -> Channel {
+        match self {
+            Channel::Float(values) => Channel::Float(indices.iter().map(|&i| values[i]).collect()),
+            Channel::Integer(values) => Channel::Integer(indices.iter().map(|&i| values[i]).collect()),
+            Channel::Boolean(values) => Channel::Boolean(indices.iter().map(|&i| values[i]).collect()),
+        }
+    }
+
+    /// Linear interpolation between pairs of points, integers are rounded and booleans take the nearer point
+    pub(crate) fn interpolate(&self, segments: &[(usize, usize, f64)]) -> Channel {
+        match self {
+            Channel::Float(values) => Channel::Float(segments.iter()
+                .map(|&(i0, i1, t)| values[i0] + (values[i1] - values[i0]) * t)
+                .collect()),
+            Channel::Integer(values) => Channel::Integer(segments.iter()
+                .map(|&(i0, i1, t)| (values[i0] as f64 + (values[i1] as f64 - values[i0] as f64) * t).round() as u64)
+                .collect()),
+            Channel::Boolean(values) => Channel::Boolean(segments.iter()
+                .map(|&(i0, i1, t)| if t < 0.5 { values[i0] } else { values[i1] })
+                .collect()),
+        }
+    }
+
+    /// Averages the values within each `(start, end)` range, booleans keep the first value of the range
+    pub(crate) fn merge(&self, ranges: &[(usize, usize)]) -> Channel {
+        match self {
+            Channel::Float(values) => Channel::Float(ranges.iter()
+                .map(|&(start, end)| values[start..end].iter().sum::<f64>() / (end - start) as f64)
+                .collect()),
+            Channel::Integer(values) => Channel::Integer(ranges.iter()
+                .map(|&(start, end)| (values[start..end].iter().map(|&v| v as f64).sum::<f64>() / (end - start) as f64).round() as u64)
+                .collect()),
+            Channel::Boolean(values) => Channel::Boolean(ranges.iter()
+                .map(|&(start, _)| values[start])
+                .collect()),
+        }
+    }
+}
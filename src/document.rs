@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::boundingbox::BoundingBox;
+use crate::sketch::Sketch;
+use crate::stroke::Stroke;
+use crate::units::Unit;
+
+/// Single page of a document, a sketch together with the page dimensions given in `unit`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Page {
+    #[serde(rename = "type")]
+    pub typ: String,
+    pub meta: HashMap<String, serde_json::Value>,
+    pub width: f64,
+    pub height: f64,
+    pub unit: Unit,
+    pub sketch: Sketch,
+}
+
+impl Page {
+    pub fn new(sketch: Sketch, width: f64, height: f64, unit: Unit) -> Page {
+        Page { typ: String::from("page"), meta: HashMap::new(), width, height, unit, sketch }
+    }
+
+    /// Area of the page, as opposed to the bounds of the ink on it
+    pub fn bounding_box(&self) -> BoundingBox {
+        BoundingBox::new(0., 0., self.width, self.height)
+    }
+}
+
+/// Ordered collection of pages, e.g. a multi-page note
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Document {
+    #[serde(rename = "type")]
+    pub typ: String,
+    pub meta: HashMap<String, serde_json::Value>,
+    pub pages: Vec<Page>,
+}
+
+impl Document {
+    pub fn new(pages: Vec<Page>) -> Document {
+        Document { typ: String::from("document"), meta: HashMap::new(), pages }
+    }
+
+    pub fn pages(&self) -> &Vec<Page> {
+        &self.pages
+    }
+
+    pub fn add_page(&mut self, page: Page) {
+        self.pages.push(page);
+    }
+
+    pub fn len(&self) -> usize {
+        self.pages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pages.is_empty()
+    }
+
+    /// Iterates over the strokes of all pages together with the index of their page
+    pub fn strokes(&self) -> impl Iterator<Item = (usize, &Stroke)> + '_ {
+        self.pages.iter().enumerate().flat_map(|(i, page)| page.sketch.strokes.iter().map(move |s| (i, s)))
+    }
+
+    /// Pages with at least one point recorded between `start` and `end`, inclusive
+    pub fn pages_between(&self, start: u64, end: u64) -> Vec<usize> {
+        self.pages.iter().enumerate()
+            .filter(|(_, page)| page.sketch.strokes.iter().any(|s| s.timestamp.iter().any(|&t| start <= t && t <= end)))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    pub fn x_min(&self) -> f64 {
+        self.pages.iter().map(|p| p.sketch.x_min()).fold(f64::MAX, f64::min)
+    }
+
+    pub fn x_max(&self) -> f64 {
+        self.pages.iter().map(|p| p.sketch.x_max()).fold(f64::MIN, f64::max)
+    }
+
+    pub fn y_min(&self) -> f64 {
+        self.pages.iter().map(|p| p.sketch.y_min()).fold(f64::MAX, f64::min)
+    }
+
+    pub fn y_max(&self) -> f64 {
+        self.pages.iter().map(|p| p.sketch.y_max()).fold(f64::MIN, f64::max)
+    }
+
+    pub fn timestamp_min(&self) -> u64 {
+        self.pages.iter().map(|p| p.sketch.timestamp_min()).fold(u64::MAX, u64::min)
+    }
+
+    pub fn timestamp_max(&self) -> u64 {
+        self.pages.iter().map(|p| p.sketch.timestamp_max()).fold(u64::MIN, u64::max)
+    }
+
+    /// Time between the first and the last recorded point of the document, 0 without timestamps
+    pub fn duration(&self) -> u64 {
+        self.timestamp_max().saturating_sub(self.timestamp_min())
+    }
+}
+
+impl fmt::Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", json!(self))
+    }
+}
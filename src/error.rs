@@ -0,0 +1,112 @@
+use std::error;
+use std::fmt;
+use std::io;
+
+use serde_json::error::Category;
+
+use crate::validation::ValidationIssue;
+
+/// Error type returned by the loading and dumping functions of this crate
+#[derive(Debug)]
+pub enum InkError {
+    /// Reading from or writing to a file or stream failed
+    Io(io::Error),
+    /// The input is not well-formed, positions are 1-based and binary input reports its byte offset as column of line 1
+    Parse { message: String, line: usize, column: usize },
+    /// The input is well-formed but does not describe a valid ink object
+    Schema(String),
+    /// Coordinates or timestamps cannot be converted between two coordinate systems
+    Conversion(String),
+    /// Strict loading found invalid strokes, issues are paired with the index of the stroke
+    Validation(Vec<(usize, ValidationIssue)>),
+    /// Strict loading found invalid strokes in a sketch of a collection, stroke indices are local to the sketch
+    SketchValidation { sketch: usize, issues: Vec<(usize, ValidationIssue)> },
+    /// Strict loading found invalid strokes on a page of a document, stroke indices are local to the page
+    PageValidation { page: usize, issues: Vec<(usize, ValidationIssue)> },
+    /// Strict loading found invalid strokes in the sketch on a line of a JSON Lines stream, 1-based
+    LineValidation { line: usize, issues: Vec<(usize, ValidationIssue)> },
+}
+
+fn write_issues(f: &mut fmt::Formatter, issues: &[(usize, ValidationIssue)]) -> fmt::Result {
+    for (i, (stroke, issue)) in issues.iter().enumerate() {
+        write!(f, "{} stroke {}: {}", if i == 0 { ":" } else { ";" }, stroke, issue)?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for InkError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InkError::Io(e) => write!(f, "I/O error: {}", e),
+            InkError::Parse { message, line, column } => write!(f, "parse error at line {} column {}: {}", line, column, message),
+            InkError::Schema(message) => write!(f, "schema error: {}", message),
+            InkError::Conversion(message) => write!(f, "conversion error: {}", message),
+            InkError::Validation(issues) => {
+                write!(f, "validation failed")?;
+                write_issues(f, issues)
+            }
+            InkError::SketchValidation { sketch, issues } => {
+                write!(f, "validation failed in sketch {}", sketch)?;
+                write_issues(f, issues)
+            }
+            InkError::PageValidation { page, issues } => {
+                write!(f, "validation failed on page {}", page)?;
+                write_issues(f, issues)
+            }
+            InkError::LineValidation { line, issues } => {
+                write!(f, "validation failed at line {}", line)?;
+                write_issues(f, issues)
+            }
+        }
+    }
+}
+
+impl error::Error for InkError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            InkError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for InkError {
+    fn from(e: io::Error) -> Self {
+        InkError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for InkError {
+    fn from(e: serde_json::Error) -> Self {
+        match e.classify() {
+            Category::Io => InkError::Io(e.into()),
+            Category::Syntax | Category::Eof => InkError::Parse { message: e.to_string(), line: e.line(), column: e.column() },
+            Category::Data => InkError::Schema(e.to_string()),
+        }
+    }
+}
+
+impl From<roxmltree::Error> for InkError {
+    fn from(e: roxmltree::Error) -> Self {
+        let position = e.pos();
+        InkError::Parse { message: e.to_string(), line: position.row as usize, column: position.col as usize }
+    }
+}
+
+impl From<png::EncodingError> for InkError {
+    fn from(e: png::EncodingError) -> Self {
+        match e {
+            png::EncodingError::IoError(e) => InkError::Io(e),
+            e => InkError::Io(io::Error::other(e)),
+        }
+    }
+}
+
+impl From<zip::result::ZipError> for InkError {
+    fn from(e: zip::result::ZipError) -> Self {
+        match e {
+            zip::result::ZipError::Io(e) => InkError::Io(e),
+            e => InkError::Schema(e.to_string()),
+        }
+    }
+}
@@ -1,18 +1,55 @@
+pub mod annotation;
 pub mod stroke;
 pub mod sketch;
+pub mod document;
 pub mod boundingbox;
+pub mod channel;
+pub mod point;
+pub mod error;
 pub mod serialization;
+pub mod raster;
+pub mod sequence;
+pub mod simplification;
+pub mod smoothing;
+pub mod style;
+pub mod transform;
+pub mod units;
+pub mod validation;
 
 #[cfg(test)]
 mod tests {
     use serde_json::json;
     use tempfile::tempdir;
 
+    use crate::annotation::TraceGroup;
     use crate::boundingbox::BoundingBox;
+    use crate::channel::Channel;
+    use crate::document::{Document, Page};
+    use crate::error::InkError;
+    use crate::point::Point;
+    use crate::raster::{self, PixelFormat, RasterOptions};
+    use crate::sequence::{self, SequenceOptions};
+    use crate::serialization::binary_serializer::{self, BinaryOptions};
+    use crate::serialization::compression::{self, Compression};
+    use crate::serialization::iam_serializer;
+    use crate::serialization::inkml_serializer;
+    use crate::serialization::{DumpOptions, LoadOptions};
+    use crate::simplification::SimplificationAlgorithm;
+    use crate::smoothing::{SmoothingFilter, SmoothingOptions};
     use crate::serialization::json_serializer;
+    use crate::serialization::jsonl_serializer::{self, SketchReader};
+    use crate::serialization::numpy_serializer::{self, NpyArray, NpyData, NumpyLayout};
+    use crate::serialization::quickdraw_serializer::{self, QuickDrawFormat, QuickDrawReader};
+    use crate::serialization::svg_serializer;
+    use crate::serialization::unipen_serializer;
+    use crate::serialization::svg_serializer::SvgOptions;
     use crate::sketch::Sketch;
     use crate::stroke::Stroke;
     use crate::stroke::StrokeBuilder;
+    use crate::style::{self, StrokeStyle, Tool};
+    use crate::transform::AffineTransform;
+    use crate::units::{CoordinateSystem, TimeUnit, Unit, YDirection};
+    use crate::validation::ValidationIssue;
 
     extern crate serde_json;
 
@@ -300,6 +337,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::assertions_on_constants)]
     fn file_writing() {
         let x = vec![1., 2., 3., 4., 5., 32.];
         let y = vec![1., 2., 3., 4., 5.];
@@ -314,8 +352,8 @@ mod tests {
         let file_path_stroke = String::from(dir.path().join("temp_stroke.json").to_str().unwrap());
         let file_path_sketch = String::from(dir.path().join("temp_sketch.json").to_str().unwrap());
 
-        json_serializer::dump_stroke(&stroke, &file_path_stroke);
-        json_serializer::dump_sketch(&sketch, &file_path_sketch);
+        json_serializer::dump_stroke(&stroke, &file_path_stroke).unwrap();
+        json_serializer::dump_sketch(&sketch, &file_path_sketch).unwrap();
 
         let loaded_stroke = json_serializer::load_stroke(&file_path_stroke).unwrap();
         let loaded_sketch = json_serializer::load_sketch(&file_path_sketch).unwrap();
@@ -404,6 +442,7 @@ mod tests {
         assert_eq!(sketch.strokes[1].y, y_scaled);
     }
 
+    #[allow(clippy::needless_return)]
     fn generate_sketch() -> Sketch {
         let x1 = vec![1., 2.];
         let y1 = vec![0.9, 0.3];
@@ -418,6 +457,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::unnecessary_cast)]
     fn sketch_normalize_default() {
         let mut sketch = generate_sketch();
         sketch.normalize(1., false);
@@ -486,4 +526,1183 @@ mod tests {
         sketch.remove_single_dot_strokes();
         assert_eq!(sketch.len(), 2);
     }
+
+    #[test]
+    fn inkml_serialization() {
+        let s1 = Stroke::new(vec![10., 20., 30.5], vec![1., 2., 3.], vec![100, 110, 120], vec![0.5, 0.75, 1.]);
+        let s2 = Stroke::new(vec![-4., 5.], vec![6., 7.], vec![130, 140], vec![0.25, 0.5]);
+        let mut sketch = Sketch::new(vec![s1, s2]);
+        sketch.meta.insert(String::from("truth"), json!("a < b"));
+
+        let inkml = inkml_serializer::dumps_sketch(&sketch);
+        let loaded = inkml_serializer::loads_sketch(inkml).unwrap();
+
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded.meta.get("truth").unwrap(), &json!("a < b"));
+        for (original, stroke) in sketch.strokes.iter().zip(loaded.strokes.iter()) {
+            assert_eq!(stroke.x, original.x);
+            assert_eq!(stroke.y, original.y);
+            assert_eq!(stroke.timestamp, original.timestamp);
+            assert_eq!(stroke.pressure, original.pressure);
+        }
+
+        let stroke = inkml_serializer::loads_stroke(inkml_serializer::dumps_stroke(&sketch.strokes[1])).unwrap();
+        assert_eq!(stroke.x, sketch.strokes[1].x);
+    }
+
+    #[test]
+    fn inkml_trace_groups_and_difference_encoding() {
+        let inkml = String::from(r##"<ink xmlns="http://www.w3.org/2003/InkML">
+            <definitions>
+                <context xml:id="ctx">
+                    <traceFormat>
+                        <channel name="X" type="decimal"/>
+                        <channel name="Y" type="decimal"/>
+                        <channel name="F" type="decimal"/>
+                    </traceFormat>
+                </context>
+            </definitions>
+            <annotation type="writer">w01</annotation>
+            <trace xml:id="a" contextRef="#ctx">10 0 1, '1 '2 '0, "1 "0 "0</trace>
+            <traceGroup xml:id="g0">
+                <annotation type="truth">x</annotation>
+                <traceView traceDataRef="#a"/>
+                <traceGroup>
+                    <trace contextRef="#ctx">1 2 3, 4 5 6</trace>
+                </traceGroup>
+            </traceGroup>
+        </ink>"##);
+
+        let sketch = inkml_serializer::loads_sketch(inkml).unwrap();
+        assert_eq!(sketch.len(), 2);
+        assert_eq!(sketch.meta.get("writer").unwrap(), &json!("w01"));
+        assert_eq!(sketch.strokes[0].x, [10., 11., 13.]);
+        assert_eq!(sketch.strokes[0].y, [0., 2., 4.]);
+        assert_eq!(sketch.strokes[0].pressure, [1., 1., 1.]);
+        assert!(sketch.strokes[0].timestamp.is_empty());
+        assert_eq!(sketch.strokes[1].x, [1., 4.]);
+
+        let group = &sketch.groups[0];
+        assert_eq!(group.name, "g0");
+        assert_eq!(group.label, Some(String::from("x")));
+        assert_eq!(group.stroke_indices(), [0, 1]);
+        assert_eq!(group.groups[0].stroke_indices(), [1]);
+
+        let reloaded = inkml_serializer::loads_sketch(inkml_serializer::dumps_sketch(&sketch)).unwrap();
+        assert_eq!(reloaded.meta, sketch.meta);
+        assert_eq!(reloaded.groups, sketch.groups);
+        assert_eq!(reloaded.strokes[1].x, sketch.strokes[1].x);
+    }
+
+    #[test]
+    fn inkml_mixed_channels_and_typed_annotations() {
+        let s1 = Stroke::new(vec![1., 2.], vec![3., 4.], vec![10, 20], vec![0.5, 1.]);
+        let s2 = Stroke::new(vec![5., 6.], vec![7., 8.], vec![], vec![0.25, 0.75]);
+        let s3 = Stroke::new(vec![9.], vec![10.], vec![30], vec![]);
+        let mut sketch = Sketch::new(vec![s1, s2, s3]);
+        sketch.meta.insert(String::from("id"), json!(1));
+        sketch.meta.insert(String::from("checked"), json!(true));
+        sketch.meta.insert(String::from("writer"), json!({"hand": "left"}));
+        sketch.meta.insert(String::from("number"), json!("1"));
+
+        let loaded = inkml_serializer::loads_sketch(inkml_serializer::dumps_sketch(&sketch)).unwrap();
+        assert_eq!(loaded.meta, sketch.meta);
+        for (original, stroke) in sketch.strokes.iter().zip(loaded.strokes.iter()) {
+            assert_eq!(stroke.x, original.x);
+            assert_eq!(stroke.timestamp, original.timestamp);
+            assert_eq!(stroke.pressure, original.pressure);
+        }
+
+        let inkml = String::from(r#"<ink xmlns="http://www.w3.org/2003/InkML">
+            <traceFormat>
+                <channel name="X" type="decimal"/>
+                <channel name="B" type="boolean"/>
+                <channel name="Y" type="decimal"/>
+            </traceFormat>
+            <trace>1 T 2, 3 F 4</trace>
+        </ink>"#);
+        let sketch = inkml_serializer::loads_sketch(inkml).unwrap();
+        assert_eq!(sketch.strokes[0].x, [1., 3.]);
+        assert_eq!(sketch.strokes[0].y, [2., 4.]);
+        assert_eq!(sketch.strokes[0].channels["B"], Channel::Boolean(vec![true, false]));
+
+        let mut s1 = Stroke::new(vec![1., 2.], vec![3., 4.], vec![10, 20], vec![]);
+        s1.channels.insert(String::from("tilt"), Channel::Float(vec![0.5, 0.25]));
+        s1.channels.insert(String::from("button"), Channel::Boolean(vec![true, false]));
+        s1.style = StrokeStyle::new("#ff0000", 2., 0.75, Tool::Marker);
+        let mut s2 = Stroke::new(vec![5.], vec![6.], vec![30], vec![]);
+        s2.channels.insert(String::from("azimuth"), Channel::Integer(vec![7]));
+        s2.style.width = Some(3.);
+        let mut sketch = Sketch::new(vec![s1, s2, Stroke::new(vec![7.], vec![8.], vec![40], vec![])]);
+        sketch.style = StrokeStyle { color: Some(String::from("blue")), ..StrokeStyle::default() };
+        let inkml = inkml_serializer::dumps_sketch(&sketch);
+        assert!(inkml.contains(r#"<brushProperty name="tool" value="marker"/>"#));
+        let loaded = inkml_serializer::loads_sketch(inkml).unwrap();
+        assert_eq!(loaded.style, sketch.style);
+        for (original, stroke) in sketch.strokes.iter().zip(loaded.strokes.iter()) {
+            assert_eq!(stroke.channels, original.channels);
+            assert_eq!(stroke.style, original.style);
+        }
+    }
+
+    #[test]
+    fn svg_export() {
+        let s1 = Stroke::new(vec![0., 10., 20.], vec![0., 5., 10.], vec![1, 2, 3], vec![0.1, 0.5, 1.]);
+        let s2 = Stroke::new(vec![5.], vec![5.], vec![4], vec![1.]);
+        let sketch = Sketch::new(vec![s1, s2]);
+
+        let options = SvgOptions { margin: 2., stroke_color: String::from("red"), ..SvgOptions::default() };
+        let svg = svg_serializer::dumps_sketch(&sketch, &options);
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains("viewBox=\"-2 -2 24 14\""));
+        assert!(svg.contains("<path d=\"M0 0 L10 5 L20 10\" fill=\"none\" stroke=\"red\""));
+        assert_eq!(svg.matches("<circle").count(), 1);
+
+        let options = SvgOptions { pressure_sensitive: true, background_color: Some(String::from("white")), ..SvgOptions::default() };
+        let svg = svg_serializer::dumps_sketch(&sketch, &options);
+        assert!(svg.contains("<rect"));
+        assert!(svg.contains(" Z\" fill=\"black\""));
+
+        let hostile = String::from("red\"/><script>alert('x')</script><g a=\"");
+        let options = SvgOptions { stroke_color: hostile.clone(), background_color: Some(hostile.clone()), ..SvgOptions::default() };
+        let svg = svg_serializer::dumps_sketch(&sketch, &options);
+        assert!(!svg.contains("<script"));
+        let document = roxmltree::Document::parse(&svg).unwrap();
+        assert!(document.descendants().all(|n| n.tag_name().name() != "script"));
+        assert_eq!(document.descendants().find(|n| n.has_tag_name("rect")).unwrap().attribute("fill"), Some(hostile.as_str()));
+
+        let dot = Sketch::new(vec![Stroke::new(vec![5.], vec![5.], vec![], vec![])]);
+        let svg = svg_serializer::dumps_sketch(&dot, &SvgOptions { margin: 0., ..SvgOptions::default() });
+        assert!(svg.contains("viewBox=\"4 4 2 2\""));
+        assert!(!svg.contains("<g"));
+
+        let mut upright = sketch.clone();
+        upright.coordinate_system.y_direction = YDirection::Up;
+        let svg = svg_serializer::dumps_sketch(&upright, &options);
+        let document = roxmltree::Document::parse(&svg).unwrap();
+        let group = document.descendants().find(|n| n.has_tag_name("g")).unwrap();
+        assert_eq!(group.attribute("transform"), Some("matrix(1 0 0 -1 0 10)"));
+        assert_eq!(group.children().filter(|n| n.is_element()).count(), 2);
+    }
+
+    #[test]
+    fn raster_rendering() {
+        let s1 = Stroke::new(vec![0., 100.], vec![0., 0.], vec![1, 2], vec![1., 1.]);
+        let s2 = Stroke::new(vec![0., 100.], vec![50., 50.], vec![3, 4], vec![1., 1.]);
+        let sketch = Sketch::new(vec![s1, s2]);
+
+        let options = RasterOptions { width: 64, height: 64, margin: 4., line_width: 3., ..RasterOptions::default() };
+        let image = raster::render_sketch(&sketch, &options);
+        assert_eq!(image.pixels.len(), 64 * 64);
+        assert_eq!(image.pixel(32, 17)[0], 0);
+        assert_eq!(image.pixel(32, 46)[0], 0);
+        assert_eq!(image.pixel(32, 32)[0], 255);
+        assert_eq!(image.pixel(0, 0)[0], 255);
+        assert!(image.pixels.iter().any(|&p| p > 0 && p < 255));
+
+        let options = RasterOptions { format: PixelFormat::Rgba, foreground: [255, 0, 0, 255], ..options };
+        let image = raster::render_sketch(&sketch, &options);
+        assert_eq!(image.pixel(32, 17), [255, 0, 0, 255]);
+
+        let png = image.to_png().unwrap();
+        assert_eq!(&png[..8], &[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]);
+    }
+
+    #[test]
+    fn errors() {
+        let dir = tempdir().unwrap();
+        let missing = String::from(dir.path().join("missing.json").to_str().unwrap());
+        match json_serializer::load_sketch(&missing) {
+            Err(InkError::Io(_)) => {}
+            other => panic!("expected I/O error, got {:?}", other),
+        }
+
+        let unwritable = String::from(dir.path().join("missing").join("sketch.json").to_str().unwrap());
+        let sketch = generate_sketch();
+        assert!(matches!(json_serializer::dump_sketch(&sketch, &unwritable), Err(InkError::Io(_))));
+
+        match json_serializer::loads_sketch(String::from("{\n  \"type\": \"sketch\",\n  \"meta\": {,\n}")) {
+            Err(InkError::Parse { line, column, .. }) => {
+                assert_eq!(line, 3);
+                assert_eq!(column, 12);
+            }
+            other => panic!("expected parse error, got {:?}", other),
+        }
+
+        match json_serializer::loads_stroke(String::from("{\"type\": \"stroke\", \"meta\": {}}")) {
+            Err(InkError::Schema(message)) => assert!(message.contains("missing field")),
+            other => panic!("expected schema error, got {:?}", other),
+        }
+
+        assert!(matches!(inkml_serializer::loads_sketch(String::from("<ink><trace>1 2, 3</trace></ink>")), Err(InkError::Schema(_))));
+        assert!(matches!(inkml_serializer::loads_sketch(String::from("<ink>")), Err(InkError::Parse { .. })));
+    }
+
+    #[test]
+    fn validation() {
+        let stroke = Stroke::new(vec![1., 2., 3.], vec![1., 2., 3.], vec![1, 2, 3], vec![]);
+        assert_eq!(stroke.validate(), Ok(()));
+
+        let stroke = Stroke::new(vec![1., f64::NAN, 3.], vec![1., 2.], vec![1, 3, 2], vec![1., 1., f64::INFINITY]);
+        let issues = stroke.validate().unwrap_err();
+        assert_eq!(issues, vec![
+            ValidationIssue::LengthMismatch { channel: String::from("y"), expected: 3, found: 2 },
+            ValidationIssue::NonFiniteValue { channel: String::from("x"), index: 1 },
+            ValidationIssue::NonFiniteValue { channel: String::from("pressure"), index: 2 },
+            ValidationIssue::NonMonotonicTimestamp { index: 2 },
+        ]);
+
+        let empty = Stroke::new(vec![], vec![], vec![], vec![]);
+        let sketch = Sketch::new(vec![generate_sketch().strokes[0].clone(), empty]);
+        assert_eq!(sketch.validate(), Err(vec![(1, ValidationIssue::EmptyStroke)]));
+
+        let mut stroke = Stroke::new(vec![1., 1., 2.], vec![1., 1., 2.], vec![1, 2], vec![]);
+        stroke.remove_duplicate_dots();
+        assert_eq!(stroke.len(), 2);
+    }
+
+    #[test]
+    fn strict_loading() {
+        let stroke = Stroke::new(vec![1., 2., 3.], vec![1., 2.], vec![1, 2, 3], vec![1., 1., 1.]);
+        let sketch = Sketch::new(vec![stroke]);
+        let json_sketch = json_serializer::dumps_sketch(&sketch);
+
+        assert!(json_serializer::loads_sketch(json_sketch.clone()).is_ok());
+        match json_serializer::loads_sketch_with_options(json_sketch, &LoadOptions::strict()) {
+            Err(InkError::Validation(issues)) => {
+                assert_eq!(issues, vec![(0, ValidationIssue::LengthMismatch { channel: String::from("y"), expected: 3, found: 2 })]);
+            }
+            other => panic!("expected validation error, got {:?}", other),
+        }
+
+        let json_sketch = json_serializer::dumps_sketch(&generate_sketch());
+        assert!(json_serializer::loads_sketch_with_options(json_sketch, &LoadOptions::strict()).is_ok());
+
+        let valid = generate_sketch().strokes[0].clone();
+        let mut invalid = valid.clone();
+        invalid.y.truncate(1);
+        let json_strokes = json_serializer::dumps_strokes(&[valid.clone(), valid.clone(), invalid.clone()]);
+        let error = json_serializer::loads_strokes_with_options(json_strokes.clone(), &LoadOptions::strict()).unwrap_err();
+        assert!(matches!(&error, InkError::Validation(issues) if issues[0].0 == 2));
+        assert!(error.to_string().starts_with("validation failed: stroke 2:"));
+        let error = json_serializer::read_strokes_with_options(json_strokes.as_bytes(), &LoadOptions::strict()).unwrap_err();
+        assert!(matches!(error, InkError::Validation(issues) if issues[0].0 == 2));
+
+        let sketches = vec![generate_sketch(), Sketch::new(vec![valid, invalid])];
+        let json_sketches = json_serializer::dumps_sketches(&sketches);
+        for result in [
+            json_serializer::loads_sketches_with_options(json_sketches.clone(), &LoadOptions::strict()),
+            json_serializer::read_sketches_with_options(json_sketches.as_bytes(), &LoadOptions::strict()),
+            binary_serializer::loads_sketches_with_options(binary_serializer::dumps_sketches(&sketches), &LoadOptions::strict()),
+        ] {
+            match result {
+                Err(InkError::SketchValidation { sketch, issues }) => assert_eq!((sketch, issues[0].0), (1, 1)),
+                other => panic!("expected sketch validation error, got {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn spatial_resampling() {
+        let x = vec![0., 10., 10.];
+        let y = vec![0., 0., 5.];
+        let timestamp = vec![0, 100, 150];
+        let pressure = vec![0., 1., 0.5];
+        let stroke = Stroke::new(x, y, timestamp, pressure);
+        assert_eq!(stroke.arc_length(), 15.);
+
+        let mut resampled = stroke.clone();
+        resampled.resample_by_distance(4.);
+        assert_eq!(resampled.x, [0., 4., 8., 10., 10.]);
+        assert_eq!(resampled.y, [0., 0., 0., 2., 5.]);
+        assert_eq!(resampled.timestamp, [0, 40, 80, 120, 150]);
+        assert_eq!(resampled.pressure[1], 0.4);
+        assert_eq!(resampled.pressure[4], 0.5);
+
+        let mut resampled = stroke.clone();
+        resampled.resample_to_count(4);
+        assert_eq!(resampled.x, [0., 5., 10., 10.]);
+        assert_eq!(resampled.y, [0., 0., 0., 5.]);
+        assert_eq!(resampled.timestamp, [0, 50, 100, 150]);
+
+        let mut sketch = Sketch::new(vec![stroke.clone(), Stroke::new(vec![1., 1.], vec![2., 2.], vec![], vec![])]);
+        sketch.resample_to_count(3);
+        assert_eq!(sketch.strokes[0].len(), 3);
+        assert_eq!(sketch.strokes[1].x, [1., 1., 1.]);
+        assert!(sketch.strokes[1].timestamp.is_empty());
+    }
+
+    #[test]
+    fn temporal_resampling() {
+        let x = vec![0., 30., 10., 10., 20.];
+        let y = vec![0., 3., 2., 0., 2.];
+        let timestamp = vec![0, 30, 10, 10, 20];
+        let pressure = vec![0., 3., 1., 1., 2.];
+        let mut stroke = Stroke::new(x, y, timestamp, pressure);
+
+        stroke.resample_by_interval(5.);
+        assert_eq!(stroke.timestamp, [0, 5, 10, 15, 20, 25, 30]);
+        assert_eq!(stroke.x, [0., 5., 10., 15., 20., 25., 30.]);
+        assert_eq!(stroke.y, [0., 0.5, 1., 1.5, 2., 2.5, 3.]);
+        assert_eq!(stroke.pressure, [0., 0.5, 1., 1.5, 2., 2.5, 3.]);
+
+        let mut off_grid = Stroke::new(vec![0., 14.], vec![0., 7.], vec![0, 7], vec![]);
+        off_grid.resample_by_interval(5.);
+        assert_eq!(off_grid.timestamp, [0, 5, 7]);
+        assert_eq!(off_grid.x, [0., 10., 14.]);
+        assert_eq!(off_grid.y.last(), Some(&7.));
+
+        let s1 = Stroke::new(vec![0., 1., 2.], vec![0., 0., 0.], vec![0, 10, 20], vec![]);
+        let s2 = Stroke::new(vec![5.], vec![5.], vec![], vec![]);
+        let mut sketch = Sketch::new(vec![s1, s2]);
+        sketch.resample_by_frequency(200.);
+        assert_eq!(sketch.strokes[0].timestamp, [0, 5, 10, 15, 20]);
+        assert_eq!(sketch.strokes[0].x, [0., 0.5, 1., 1.5, 2.]);
+        assert_eq!(sketch.strokes[1].x, [5.]);
+    }
+
+    #[test]
+    fn simplification() {
+        let x = vec![0., 1., 2., 3., 4., 5., 6.];
+        let y = vec![0., 0.1, -0.1, 5., 6., 7.05, 8.];
+        let timestamp = vec![0, 1, 2, 3, 4, 5, 6];
+        let pressure = vec![0., 0.1, 0.2, 0.3, 0.4, 0.5, 0.6];
+        let stroke = Stroke::new(x, y, timestamp, pressure);
+
+        let mut rdp = stroke.clone();
+        rdp.simplify(0.5, SimplificationAlgorithm::RamerDouglasPeucker);
+        assert_eq!(rdp.x, [0., 2., 3., 6.]);
+        assert_eq!(rdp.timestamp, [0, 2, 3, 6]);
+        assert_eq!(rdp.pressure, [0., 0.2, 0.3, 0.6]);
+
+        let mut vw = stroke.clone();
+        vw.simplify(0.5, SimplificationAlgorithm::VisvalingamWhyatt);
+        assert_eq!(vw.x, [0., 2., 3., 6.]);
+        assert_eq!(vw.timestamp, [0, 2, 3, 6]);
+
+        let mut sketch = Sketch::new(vec![stroke.clone(), Stroke::new(vec![0., 1., 2., 3.], vec![0.; 4], vec![], vec![])]);
+        let ratio = sketch.simplify(0.5, SimplificationAlgorithm::RamerDouglasPeucker);
+        assert_eq!(sketch.strokes[1].x, [0., 3.]);
+        assert_eq!(ratio, 11. / 6.);
+    }
+
+    #[test]
+    fn smoothing() {
+        let values = [0., 3., 0., 3., 0.];
+        assert_eq!(SmoothingFilter::MovingAverage { window: 3 }.apply(&values, &[]), [1.5, 1., 2., 1., 1.5]);
+
+        let gaussian = SmoothingFilter::Gaussian { sigma: 1. }.apply(&[0., 0., 6., 0., 0.], &[]);
+        assert!(gaussian[2] < 6. && gaussian[1] > 0. && gaussian[1] < gaussian[2]);
+        assert!((gaussian[1] - gaussian[3]).abs() < 1e-12);
+
+        let quadratic: Vec<f64> = (0..7).map(|i| (i * i) as f64).collect();
+        let savitzky_golay = SmoothingFilter::SavitzkyGolay { window: 5, order: 2 }.apply(&quadratic, &[]);
+        for (smoothed, original) in savitzky_golay.iter().zip(quadratic.iter()) {
+            assert!((smoothed - original).abs() < 1e-9);
+        }
+
+        let one_euro = SmoothingFilter::OneEuro { min_cutoff: 1., beta: 0., derivative_cutoff: 1., frequency: 100. };
+        let step = [0., 10., 10., 10.];
+        let filtered = one_euro.apply(&step, &[0, 10, 20, 30]);
+        assert_eq!(filtered[0], 0.);
+        assert!(filtered[1] > 0. && filtered[1] < filtered[2] && filtered[3] < 10.);
+        assert_eq!(one_euro.apply(&step, &[]), filtered);
+
+        let stroke = Stroke::new(values.to_vec(), values.to_vec(), vec![], values.to_vec());
+        let mut sketch = Sketch::new(vec![stroke]);
+        sketch.smooth(&SmoothingOptions { position: Some(SmoothingFilter::MovingAverage { window: 3 }), pressure: None });
+        assert_eq!(sketch.strokes[0].x, [1.5, 1., 2., 1., 1.5]);
+        assert_eq!(sketch.strokes[0].y, [1.5, 1., 2., 1., 1.5]);
+        assert_eq!(sketch.strokes[0].pressure, values);
+    }
+
+    fn assert_close(actual: &[f64], expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected.iter()) {
+            assert!((a - e).abs() < 1e-9, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn affine_transform() {
+        let transform = AffineTransform::rotation(std::f64::consts::FRAC_PI_2)
+            .then(&AffineTransform::scaling(2., 3.))
+            .then(&AffineTransform::translation(1., -1.));
+        let (x, y) = transform.apply(1., 0.);
+        assert_close(&[x, y], &[1., 2.]);
+
+        let inverse = transform.inverse().unwrap();
+        let (x, y) = inverse.apply(1., 2.);
+        assert_close(&[x, y], &[1., 0.]);
+        let identity = transform.then(&inverse);
+        assert_close(&[identity.a, identity.b, identity.c, identity.d, identity.e, identity.f], &[1., 0., 0., 0., 1., 0.]);
+        assert_eq!(AffineTransform::scaling(0., 1.).inverse(), None);
+
+        let mut stroke = Stroke::new(vec![0., 2.], vec![0., 0.], vec![], vec![]);
+        stroke.rotate(std::f64::consts::PI, None);
+        assert_close(&stroke.x, &[2., 0.]);
+        assert_close(&stroke.y, &[0., 0.]);
+
+        let mut sketch = Sketch::new(vec![Stroke::new(vec![0., 1.], vec![0., 0.], vec![], vec![])]);
+        sketch.rotate(std::f64::consts::FRAC_PI_2, Some((0., 0.)));
+        assert_close(&sketch.strokes[0].x, &[0., 0.]);
+        assert_close(&sketch.strokes[0].y, &[0., 1.]);
+
+        sketch.apply_transform(&AffineTransform::shear(1., 0.));
+        assert_close(&sketch.strokes[0].x, &[0., 1.]);
+
+        let bb = BoundingBox::new(0., 0., 2., 1.).apply_transform(&AffineTransform::rotation_about(std::f64::consts::FRAC_PI_2, 1., 0.5));
+        assert_close(&[bb.x_min, bb.y_min, bb.x_max, bb.y_max], &[0.5, -0.5, 1.5, 1.5]);
+    }
+
+    #[test]
+    fn extra_channels() {
+        let mut stroke = Stroke::new(vec![0., 0., 2., 4.], vec![0., 0., 0., 0.], vec![0, 1, 2, 3], vec![]);
+        stroke.set_channel("tiltX", Channel::Float(vec![0.1, 0.2, 0.3, 0.4]));
+        stroke.set_channel("azimuth", Channel::Integer(vec![10, 20, 30, 40]));
+        stroke.set_channel("button", Channel::Boolean(vec![false, false, true, true]));
+        assert_eq!(stroke.validate(), Ok(()));
+
+        let json_stroke = json_serializer::dumps_stroke(&stroke);
+        let loaded = json_serializer::loads_stroke(json_stroke).unwrap();
+        assert_eq!(loaded, stroke);
+        assert!(!json_serializer::dumps_stroke(&generate_sketch().strokes[0]).contains("channels"));
+
+        let mut deduplicated = stroke.clone();
+        deduplicated.remove_duplicate_dots();
+        assert_eq!(deduplicated.channel("azimuth"), Some(&Channel::Integer(vec![10, 30, 40])));
+        assert_eq!(deduplicated.validate(), Ok(()));
+
+        let mut resampled = stroke.clone();
+        resampled.resample_by_distance(1.);
+        assert_eq!(resampled.x, [0., 1., 2., 3., 4.]);
+        assert_eq!(resampled.channel("azimuth"), Some(&Channel::Integer(vec![20, 25, 30, 35, 40])));
+        assert_eq!(resampled.channel("button"), Some(&Channel::Boolean(vec![false, true, true, true, true])));
+        assert_eq!(resampled.validate(), Ok(()));
+
+        let mut head = stroke.clone();
+        let tail = head.split_off(1);
+        assert_eq!(head.channel("tiltX"), Some(&Channel::Float(vec![0.1])));
+        assert_eq!(tail.channel("tiltX"), Some(&Channel::Float(vec![0.2, 0.3, 0.4])));
+        assert_eq!(tail.timestamp, [1, 2, 3]);
+
+        stroke.set_channel("twist", Channel::Float(vec![1.]));
+        assert_eq!(stroke.validate(), Err(vec![ValidationIssue::LengthMismatch { channel: String::from("twist"), expected: 4, found: 1 }]));
+    }
+
+    #[test]
+    fn points() {
+        let stroke = Stroke::new(vec![1., 2., 3.], vec![4., 5., 6.], vec![7, 8, 9], vec![]);
+        let points: Vec<Point> = stroke.points().collect();
+        assert_eq!(points[1], Point::new(2., 5., Some(8), None));
+
+        let rebuilt = Stroke::from_points(&points);
+        assert_eq!(rebuilt, stroke);
+
+        let mut partial = points.clone();
+        partial[2].timestamp = None;
+        assert!(Stroke::from_points(&partial).timestamp.is_empty());
+
+        let mut stroke = stroke;
+        for p in stroke.points_mut() {
+            *p.x *= 10.;
+            if let Some(t) = p.timestamp {
+                *t += 1;
+            }
+            assert!(p.pressure.is_none());
+        }
+        assert_eq!(stroke.x, [10., 20., 30.]);
+        assert_eq!(stroke.timestamp, [8, 9, 10]);
+
+        stroke.extend(vec![Point::new(40., 7., Some(11), None)]);
+        assert_eq!(stroke.len(), 4);
+        assert_eq!(stroke.timestamp[3], 11);
+
+        let builder: StrokeBuilder = points.iter().cloned().collect();
+        assert_eq!(builder.len(), 3);
+
+        let mut recorded = Stroke::new(vec![1., 2.], vec![1., 2.], vec![10, 20], vec![0.5, 0.5]);
+        recorded.set_channel("tilt", Channel::Float(vec![0.1, 0.2]));
+        recorded.set_channel("button", Channel::Boolean(vec![false, true]));
+        recorded.extend(vec![Point::new(3., 3., None, Some(0.5)), Point::new(4., 4., Some(40), None)]);
+        assert_eq!(recorded.len(), 4);
+        assert_eq!(recorded.timestamp, [10, 20, 20, 40]);
+        assert_eq!(recorded.pressure, [0.5, 0.5, 0.5, 0.5]);
+        assert_eq!(recorded.channel("tilt"), Some(&Channel::Float(vec![0.1, 0.2, 0.2, 0.2])));
+        assert_eq!(recorded.channel("button"), Some(&Channel::Boolean(vec![false, true, true, true])));
+        assert!(recorded.validate().is_ok());
+
+        let mut unrecorded = Stroke::new(vec![1.], vec![1.], vec![], vec![]);
+        unrecorded.extend(vec![Point::new(2., 2., Some(5), Some(0.5))]);
+        assert!(unrecorded.timestamp.is_empty() && unrecorded.pressure.is_empty());
+
+        let mut builder: StrokeBuilder = partial.iter().cloned().collect();
+        builder.add_point(4., 7., 10, 1.);
+        let built = builder.build();
+        assert_eq!(built.len(), 4);
+        assert!(built.timestamp.is_empty() && built.pressure.is_empty());
+
+        let sketch = Sketch::new(vec![stroke.clone(), Stroke::from_points(&points[..1])]);
+        let flattened: Vec<(usize, Point)> = sketch.points().collect();
+        assert_eq!(flattened.len(), 5);
+        assert_eq!(flattened[4], (1, points[0]));
+    }
+
+    #[test]
+    fn trace_groups() {
+        let stroke = |x: f64| Stroke::new(vec![x, x + 1.], vec![0., 1.], vec![], vec![]);
+        let dot = Stroke::new(vec![5.], vec![5.], vec![], vec![]);
+        let mut sketch = Sketch::new(vec![stroke(0.), dot, stroke(2.), stroke(4.)]);
+
+        let mut word = TraceGroup::with_label("word", "hi");
+        let mut h = TraceGroup::with_label("char", "h");
+        h.add_stroke(0);
+        h.add_stroke(1);
+        let mut i = TraceGroup::with_label("char", "i");
+        i.add_stroke_range(2, 0, 1);
+        i.attributes.insert(String::from("confidence"), json!(0.9));
+        word.add_group(h);
+        word.add_group(i);
+        let mut line = TraceGroup::new("line");
+        line.add_group(word);
+        line.add_stroke(3);
+        sketch.add_group(line);
+
+        assert_eq!(sketch.groups()[0].stroke_indices(), [0, 1, 2, 3]);
+        assert_eq!(sketch.find_group("char").unwrap().label, Some(String::from("h")));
+        assert!(sketch.find_group("paragraph").is_none());
+
+        let reloaded = json_serializer::loads_sketch(json_serializer::dumps_sketch(&sketch)).unwrap();
+        assert_eq!(reloaded, sketch);
+        assert!(!json_serializer::dumps_sketch(&Sketch::new(vec![])).contains("groups"));
+
+        sketch.remove_single_dot_strokes();
+        assert_eq!(sketch.len(), 3);
+        let word = sketch.find_group("word").unwrap();
+        assert_eq!(word.groups[0].stroke_indices(), [0]);
+        assert_eq!(word.groups[1].strokes[0].stroke, 1);
+        assert_eq!(word.groups[1].strokes[0].points, Some((0, 1)));
+
+        let removed = sketch.remove_stroke(0);
+        assert_eq!(removed.x, [0., 1.]);
+        assert_eq!(sketch.groups()[0].stroke_indices(), [0, 1]);
+        assert!(sketch.find_group("char").unwrap().strokes.is_empty());
+
+        let stroke = Stroke::new(vec![0., 1., 1., 2., 3., 4.], vec![0., 1., 1., 2., 3., 4.], vec![], vec![]);
+        let mut sketch = Sketch::new(vec![stroke, Stroke::new(vec![5.], vec![5.], vec![], vec![])]);
+        let mut word = TraceGroup::new("word");
+        word.add_stroke_range(0, 3, 5);
+        word.add_stroke(1);
+        sketch.add_group(word);
+
+        sketch.remove_duplicate_dots();
+        assert_eq!(sketch.groups[0].strokes[0].points, Some((2, 4)));
+        assert_eq!(sketch.strokes[0].x[2..4], [2., 3.]);
+
+        sketch.simplify(0.1, SimplificationAlgorithm::RamerDouglasPeucker);
+        assert_eq!(sketch.strokes[0].x, [0., 4.]);
+        assert_eq!(sketch.groups[0].strokes[0].points, None);
+
+        let mut split = Sketch::new(vec![Stroke::new(vec![0., 1., 2., 3.], vec![0., 0., 0., 0.], vec![], vec![])]);
+        let mut word = TraceGroup::new("word");
+        word.add_stroke_range(0, 1, 3);
+        word.add_stroke(0);
+        split.add_group(word);
+        split.split_stroke(0, 2);
+        assert_eq!(split.len(), 2);
+        let references: Vec<(usize, Option<(usize, usize)>)> = split.groups[0].strokes.iter().map(|r| (r.stroke, r.points)).collect();
+        assert_eq!(references, [(0, Some((1, 2))), (1, Some((0, 1))), (0, None), (1, None)]);
+
+        split.resample_to_count(3);
+        assert!(split.groups[0].strokes.iter().all(|r| r.points.is_none()));
+    }
+
+    #[test]
+    fn document() {
+        let first = Sketch::new(vec![Stroke::new(vec![1., 5.], vec![2., 3.], vec![100, 200], vec![])]);
+        let second = Sketch::new(vec![Stroke::new(vec![-1., 0.], vec![4., 8.], vec![1000, 1500], vec![])]);
+        let mut document = Document::new(vec![Page::new(first, 210., 297., Unit::Millimeter)]);
+        let mut page = Page::new(second, 210., 297., Unit::Millimeter);
+        page.meta.insert(String::from("background"), json!("lined"));
+        document.add_page(page);
+
+        assert_eq!(document.len(), 2);
+        assert_eq!(document.strokes().count(), 2);
+        assert_eq!((document.x_min(), document.x_max()), (-1., 5.));
+        assert_eq!((document.y_min(), document.y_max()), (2., 8.));
+        assert_eq!((document.timestamp_min(), document.timestamp_max()), (100, 1500));
+        assert_eq!(document.duration(), 1400);
+        assert_eq!(document.pages_between(150, 1000), [0, 1]);
+        assert_eq!(document.pages_between(300, 900), Vec::<usize>::new());
+        assert_eq!(document.pages[0].bounding_box().height, 297.);
+
+        let json_string = json_serializer::dumps_document(&document);
+        assert!(json_string.contains("\"type\": \"document\""));
+        assert!(json_string.contains("\"type\": \"page\""));
+        assert!(json_string.contains("\"unit\": \"millimeter\""));
+        assert_eq!(json_serializer::loads_document(json_string).unwrap(), document);
+
+        let invalid = String::from(r#"{"type": "document", "meta": {}, "pages": [{"type": "page", "meta": {}, "width": 1, "height": 1, "unit": "px",
+            "sketch": {"type": "sketch", "meta": {}, "strokes": [{"type": "stroke", "meta": {}, "x": [1], "y": [], "timestamp": [], "pressure": []}]}}]}"#);
+        assert_eq!(json_serializer::loads_document(invalid.clone()).unwrap().pages[0].unit, Unit::Pixel);
+        assert!(matches!(json_serializer::loads_document_with_options(invalid, &LoadOptions::strict()), Err(InkError::PageValidation { page: 0, .. })));
+
+        let mut invalid = document.clone();
+        invalid.pages[1].sketch.strokes[0].timestamp = vec![2000, 1500];
+        invalid.pages[1].sketch.add_stroke(Stroke::new(vec![], vec![], vec![], vec![]));
+        let error = json_serializer::loads_document_with_options(json_serializer::dumps_document(&invalid), &LoadOptions::strict()).unwrap_err();
+        match &error {
+            InkError::PageValidation { page, issues } => {
+                assert_eq!(*page, 1);
+                assert_eq!(issues.iter().map(|(stroke, _)| *stroke).collect::<Vec<usize>>(), [0, 1]);
+            }
+            e => panic!("unexpected error {}", e),
+        }
+        assert!(error.to_string().starts_with("validation failed on page 1: stroke 0"));
+    }
+
+    #[test]
+    fn stroke_style() {
+        let mut highlight = Stroke::new(vec![0., 20.], vec![10., 10.], vec![], vec![]);
+        highlight.style = StrokeStyle { color: Some(String::from("#ff0000")), opacity: Some(0.5), tool: Some(Tool::Highlighter), ..StrokeStyle::default() };
+        let plain = Stroke::new(vec![0., 20.], vec![2., 2.], vec![], vec![]);
+        let mut sketch = Sketch::new(vec![highlight, plain]);
+        sketch.style = StrokeStyle::new("blue", 4., 1., Tool::Pen);
+
+        assert_eq!(sketch.stroke_style(0), StrokeStyle::new("#ff0000", 4., 0.5, Tool::Highlighter));
+        assert_eq!(sketch.stroke_style(1), sketch.style);
+        assert_eq!(style::parse_color("#0f08"), None);
+        assert_eq!(style::parse_color("#00ff0080"), Some([0, 255, 0, 128]));
+        assert_eq!(style::parse_color("#fff"), Some([255, 255, 255, 255]));
+
+        let json_string = json_serializer::dumps_sketch(&sketch);
+        assert!(json_string.contains("\"tool\": \"highlighter\""));
+        assert_eq!(json_serializer::loads_sketch(json_string).unwrap(), sketch);
+        assert!(!json_serializer::dumps_stroke(&sketch.strokes[1]).contains("style"));
+
+        let svg = svg_serializer::dumps_sketch(&sketch, &SvgOptions::default());
+        assert!(svg.contains("stroke=\"#ff0000\" stroke-width=\"4\""));
+        assert!(svg.contains("opacity=\"0.5\""));
+        assert!(svg.contains("stroke=\"blue\""));
+
+        let hostile = json_serializer::loads_sketch(String::from(r#"{
+            "type": "sketch",
+            "meta": {},
+            "style": {"color": "x\"/><script>alert(1)</script><g a=\""},
+            "strokes": [
+                {"type": "stroke", "meta": {}, "x": [0, 1], "y": [0, 1], "timestamp": [], "pressure": [0.5, 1]},
+                {"type": "stroke", "meta": {}, "x": [2], "y": [2], "timestamp": [], "pressure": [1], "style": {"color": "'/><script/>"}}
+            ]
+        }"#)).unwrap();
+        for pressure_sensitive in [false, true] {
+            let svg = svg_serializer::dumps_sketch(&hostile, &SvgOptions { pressure_sensitive, ..SvgOptions::default() });
+            let document = roxmltree::Document::parse(&svg).unwrap();
+            assert!(document.descendants().all(|n| n.tag_name().name() != "script"));
+            let colors: Vec<&str> = document.descendants().filter_map(|n| n.attribute("fill").filter(|&f| f != "none")).collect();
+            assert_eq!(colors.last(), Some(&"'/><script/>"));
+            assert!(document.descendants().filter_map(|n| n.attribute("stroke")).all(|c| c == hostile.style.color.as_deref().unwrap()));
+        }
+
+        let options = RasterOptions { width: 32, height: 16, format: PixelFormat::Rgba, fit_to_canvas: false, ..RasterOptions::default() };
+        let image = raster::render_sketch(&sketch, &options);
+        assert_eq!(image.pixel(10, 10), [255, 128, 128, 255]);
+        assert_eq!(image.pixel(10, 2), [0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn coordinate_system() {
+        let mut stroke = Stroke::new(vec![0., 1000.], vec![0., 500.], vec![1000, 2000], vec![]);
+        stroke.style.width = Some(100.);
+        let mut sketch = Sketch::new(vec![stroke]);
+        sketch.coordinate_system = CoordinateSystem { resolution: Some(2540.), time_unit: TimeUnit::Microsecond, ..CoordinateSystem::default() };
+
+        let millimeters = CoordinateSystem { unit: Unit::Millimeter, origin: (0., 297.), y_direction: YDirection::Up, ..CoordinateSystem::default() };
+        let mut converted = sketch.clone();
+        assert!(converted.convert_coordinate_system(&millimeters).is_ok());
+        assert_eq!(converted.strokes[0].x, vec![0., 10.]);
+        assert_eq!(converted.strokes[0].y, vec![297., 292.]);
+        assert_eq!(converted.strokes[0].timestamp, vec![1, 2]);
+        assert_eq!(converted.strokes[0].style.width, Some(1.));
+        assert_eq!(converted.coordinate_system, millimeters);
+
+        let mut dense = sketch.clone();
+        dense.strokes[0].timestamp = vec![1, 2];
+        let error = dense.convert_coordinate_system(&millimeters).unwrap_err();
+        assert_eq!(error.to_string(), "conversion error: timestamps 0 and 1 of stroke 0 fall onto the same Millisecond");
+        assert_eq!(dense.strokes[0].timestamp, vec![1, 2]);
+        assert_eq!(dense.strokes[0].x, sketch.strokes[0].x);
+
+        let mut resampled = sketch.clone();
+        resampled.strokes[0].timestamp = vec![0, 10000];
+        resampled.resample_by_frequency(200.);
+        assert_eq!(resampled.strokes[0].timestamp, vec![0, 5000, 10000]);
+
+        let one_euro = SmoothingFilter::OneEuro { min_cutoff: 1., beta: 0., derivative_cutoff: 1., frequency: 100. };
+        let step = [0., 10., 10., 10.];
+        assert_eq!(one_euro.apply_with_time_unit(&step, &[0, 10000, 20000, 30000], TimeUnit::Microsecond), one_euro.apply(&step, &[0, 10, 20, 30]));
+        let mut smoothed = Sketch::new(vec![Stroke::new(step.to_vec(), step.to_vec(), vec![0, 10000, 20000, 30000], vec![])]);
+        smoothed.coordinate_system.time_unit = TimeUnit::Microsecond;
+        smoothed.smooth(&SmoothingOptions { position: Some(one_euro), pressure: None });
+        assert_eq!(smoothed.strokes[0].x, one_euro.apply(&step, &[0, 10, 20, 30]));
+
+        assert!(converted.convert_coordinate_system(&CoordinateSystem::new(Unit::Pixel, None)).is_ok());
+        assert!((converted.x_max() - 10. / 25.4 * 96.).abs() < 1e-9);
+        assert!((converted.y_max() - 5. / 25.4 * 96.).abs() < 1e-9);
+
+        let unknown = Sketch::new(vec![Stroke::new(vec![1.], vec![2.], vec![], vec![])]);
+        let mut flipped = unknown.clone();
+        assert!(flipped.convert_coordinate_system(&CoordinateSystem { y_direction: YDirection::Up, ..CoordinateSystem::default() }).is_ok());
+        assert_eq!(flipped.strokes[0].y, vec![-2.]);
+        let error = flipped.clone().convert_coordinate_system(&millimeters).unwrap_err();
+        assert!(matches!(&error, InkError::Conversion(message) if message.starts_with("Device units")));
+
+        let json_string = json_serializer::dumps_sketch(&sketch);
+        assert!(json_string.contains("\"time_unit\": \"microsecond\""));
+        assert_eq!(json_serializer::loads_sketch(json_string).unwrap(), sketch);
+        assert!(!json_serializer::dumps_sketch(&unknown).contains("coordinate_system"));
+    }
+
+    #[test]
+    fn binary_serialization() {
+        let mut stroke = Stroke::new(vec![100., 102., 105., 103.], vec![-7., -7., 20., 1e12], vec![1600000000000, 1600000000008, 1600000000016, 1600000000015], vec![0.25, 0.5, 0.123456, 1.]);
+        stroke.channels.insert(String::from("tilt"), Channel::Integer(vec![10, 20, 30, 40]));
+        stroke.meta.insert(String::from("id"), json!(7));
+        let smooth = Stroke::new(vec![0.1, 0.2], vec![0.3, f64::NAN], vec![], vec![]);
+        let mut sketch = Sketch::new(vec![stroke, smooth, Stroke::new(vec![], vec![], vec![], vec![])]);
+        let mut word = TraceGroup::with_label("word", "hi");
+        word.add_stroke(0);
+        word.add_stroke_range(1, 0, 1);
+        sketch.add_group(word);
+        sketch.coordinate_system = CoordinateSystem::new(Unit::Millimeter, None);
+
+        let lossless = binary_serializer::dumps_sketch_with_options(&sketch, &BinaryOptions::lossless());
+        let loaded = binary_serializer::loads_sketch(lossless.clone()).unwrap();
+        assert_eq!(loaded.strokes[0], sketch.strokes[0]);
+        assert!(loaded.strokes[1].y[1].is_nan());
+        assert_eq!(loaded.groups, sketch.groups);
+        assert_eq!(loaded.coordinate_system, sketch.coordinate_system);
+        assert!(lossless.len() * 3 < json_serializer::dumps_sketch(&sketch).len());
+
+        let stroke = Stroke::new(vec![1.], vec![2.], vec![], vec![0.3]);
+        assert_eq!(binary_serializer::loads_stroke(binary_serializer::dumps_stroke(&stroke)).unwrap(), stroke);
+
+        let options = BinaryOptions { coordinate_step: Some(0.01), ..BinaryOptions::quantized() };
+        let quantized = binary_serializer::loads_sketch(binary_serializer::dumps_sketch_with_options(&sketch, &options)).unwrap();
+        assert_eq!(quantized.strokes[0].x, sketch.strokes[0].x);
+        assert!((quantized.strokes[0].pressure[2] - 0.123456).abs() <= 0.5 / 65536.);
+        assert!((quantized.strokes[1].x[1] - 0.2).abs() < 1e-12);
+
+        let sketches = vec![sketch.clone(), Sketch::new(vec![])];
+        let dir = tempdir().unwrap();
+        let file_path = String::from(dir.path().join("sketches.dink").to_str().unwrap());
+        binary_serializer::dump_sketches(&sketches, &file_path).unwrap();
+        assert_eq!(binary_serializer::load_sketches(&file_path).unwrap().len(), 2);
+
+        let strokes = vec![stroke.clone(), sketch.strokes[1].clone(), sketch.strokes[2].clone()];
+        let mut buffer = vec![];
+        binary_serializer::write_strokes(&strokes, &mut buffer, &BinaryOptions::default()).unwrap();
+        let loaded = binary_serializer::read_strokes(buffer.as_slice()).unwrap();
+        assert_eq!((loaded.len(), &loaded[0]), (3, &strokes[0]));
+        let error = binary_serializer::loads_strokes_with_options(buffer, &LoadOptions::strict()).unwrap_err();
+        assert!(matches!(error, InkError::Validation(issues) if issues[0].0 == 1));
+
+        let mut page = Page::new(Sketch::new(vec![stroke]), 210., 297., Unit::Millimeter);
+        page.meta.insert(String::from("background"), json!("lined"));
+        let mut document = Document::new(vec![page, Page::new(Sketch::new(vec![]), 100., 50., Unit::Pixel)]);
+        document.meta.insert(String::from("title"), json!("notes"));
+        let file_path = dir.path().join("document.dink");
+        binary_serializer::dump_document(&document, &file_path).unwrap();
+        assert_eq!(binary_serializer::load_document(&file_path).unwrap(), document);
+        let mut buffer = vec![];
+        binary_serializer::write_document(&document, &mut buffer, &BinaryOptions::default()).unwrap();
+        assert_eq!(binary_serializer::read_document_with_options(buffer.as_slice(), &LoadOptions::strict()).unwrap(), document);
+        assert!(matches!(binary_serializer::loads_sketches(buffer), Err(InkError::Schema(_))));
+
+        assert!(matches!(binary_serializer::loads_stroke(lossless.clone()), Err(InkError::Schema(_))));
+        assert!(matches!(binary_serializer::loads_sketch(lossless[..lossless.len() - 3].to_vec()), Err(InkError::Parse { line: 1, .. })));
+        assert!(matches!(binary_serializer::loads_sketch(b"{}".to_vec()), Err(InkError::Schema(_))));
+        assert!(matches!(binary_serializer::loads_sketch_with_options(lossless, &LoadOptions::strict()), Err(InkError::Validation(_))));
+    }
+
+    #[test]
+    fn json_lines_serialization() {
+        let sketches = vec![
+            Sketch::new(vec![Stroke::new(vec![0., 1.], vec![2., 3.], vec![10, 20], vec![])]),
+            Sketch::new(vec![]),
+        ];
+        let serialized = jsonl_serializer::dumps_sketches(&sketches);
+        assert_eq!(serialized.lines().count(), 2);
+        assert_eq!(jsonl_serializer::loads_sketches(serialized.clone()).unwrap(), sketches);
+
+        let dir = tempdir().unwrap();
+        let file_path = String::from(dir.path().join("sketches.jsonl").to_str().unwrap());
+        jsonl_serializer::dump_sketches(&sketches[..1], &file_path).unwrap();
+        let mut writer = jsonl_serializer::append_sketches(&file_path).unwrap();
+        writer.write_sketch(&sketches[1]).unwrap();
+        writer.flush().unwrap();
+        assert_eq!(jsonl_serializer::load_sketches(&file_path).unwrap(), sketches);
+        assert!(jsonl_serializer::load_sketches_with_options(&file_path, &LoadOptions::strict()).is_ok());
+
+        let broken = format!("{}\n{{\"type\" ]\n{{\"type\": \"stroke\"}}\n{}", serialized.lines().next().unwrap(), serialized);
+        let mut reader = SketchReader::new(broken.as_bytes());
+        assert_eq!(reader.next().unwrap().unwrap(), sketches[0]);
+        let error = reader.next().unwrap().unwrap_err();
+        assert!(matches!(error, InkError::Parse { line: 2, column: 9, .. }));
+        assert_eq!(error.to_string(), "parse error at line 2 column 9: expected `:`");
+        match reader.next().unwrap() {
+            Err(InkError::Schema(message)) => assert_eq!(message, "line 3: missing field `meta`"),
+            other => panic!("unexpected result {:?}", other),
+        }
+        assert_eq!(reader.filter_map(Result::ok).count(), 2);
+
+        let invalid = Sketch::new(vec![Stroke::new(vec![0., 1.], vec![2., 3.], vec![20, 10], vec![])]);
+        let lines = format!("{}\n{}", serialized, jsonl_serializer::dumps_sketches(&[invalid]));
+        let mut reader = SketchReader::with_options(lines.as_bytes(), LoadOptions::strict());
+        assert_eq!(reader.by_ref().take(2).filter(Result::is_ok).count(), 2);
+        match reader.next().unwrap() {
+            Err(InkError::LineValidation { line, issues }) => assert_eq!((line, issues[0].0), (4, 0)),
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn reader_writer_serialization() {
+        let stroke = Stroke::new(vec![0., 1.], vec![2., 3.], vec![10, 20], vec![0.5, 1.]);
+        let sketches = vec![Sketch::new(vec![stroke.clone()]), Sketch::new(vec![])];
+
+        let mut buffer = Vec::new();
+        json_serializer::write_sketches(&sketches, &mut buffer, &DumpOptions::compact()).unwrap();
+        assert!(!buffer.contains(&b'\n'));
+        assert_eq!(json_serializer::read_sketches(buffer.as_slice()).unwrap(), sketches);
+        assert_eq!(json_serializer::dumps_sketches_with_options(&sketches, &DumpOptions::compact()).into_bytes(), buffer);
+
+        let mut buffer = Vec::new();
+        json_serializer::write_stroke(&stroke, &mut buffer, &DumpOptions::default()).unwrap();
+        assert_eq!(String::from_utf8(buffer.clone()).unwrap(), json_serializer::dumps_stroke(&stroke));
+        assert_eq!(json_serializer::read_stroke(buffer.as_slice()).unwrap(), stroke);
+        assert!(matches!(json_serializer::read_stroke(&buffer[..10]), Err(InkError::Parse { .. })));
+
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("strokes.json");
+        let strokes = vec![stroke];
+        json_serializer::dump_strokes_with_options(&strokes, &file_path, &DumpOptions::compact()).unwrap();
+        assert_eq!(json_serializer::load_strokes(&file_path).unwrap(), strokes);
+        assert!(json_serializer::load_strokes_with_options(file_path, &LoadOptions::strict()).is_ok());
+    }
+
+    #[test]
+    fn compressed_files() {
+        let sketch = Sketch::new(vec![Stroke::new(vec![0.; 100], vec![1.; 100], vec![5; 100], vec![])]);
+        let dir = tempdir().unwrap();
+        let path = |name: &str| dir.path().join(name);
+
+        assert_eq!(Compression::from_path("a/sketch.json.GZ"), Compression::Gzip);
+        assert_eq!(Compression::from_path("sketch.zst"), Compression::Zstd);
+        assert_eq!(Compression::from_path("sketch.json"), Compression::None);
+
+        json_serializer::dump_sketch(&sketch, path("sketch.json.gz")).unwrap();
+        let bytes = std::fs::read(path("sketch.json.gz")).unwrap();
+        assert_eq!(Compression::from_magic(&bytes), Compression::Gzip);
+        assert!(bytes.len() < json_serializer::dumps_sketch(&sketch).len());
+        assert_eq!(json_serializer::load_sketch(path("sketch.json.gz")).unwrap(), sketch);
+
+        json_serializer::dump_sketch(&sketch, path("sketch.json.zst")).unwrap();
+        std::fs::rename(path("sketch.json.zst"), path("renamed.json")).unwrap();
+        assert_eq!(Compression::from_magic(&std::fs::read(path("renamed.json")).unwrap()), Compression::Zstd);
+        assert_eq!(json_serializer::load_sketch(path("renamed.json")).unwrap(), sketch);
+
+        let binary_path = String::from(path("sketch.dink.zst").to_str().unwrap());
+        binary_serializer::dump_sketch(&sketch, &binary_path).unwrap();
+        assert_eq!(binary_serializer::load_sketch(&binary_path).unwrap(), sketch);
+        inkml_serializer::dump_sketch(&sketch, path("sketch.inkml.gz")).unwrap();
+        assert_eq!(inkml_serializer::load_sketch(path("sketch.inkml.gz")).unwrap().strokes[0].x, sketch.strokes[0].x);
+        svg_serializer::dump_sketch(&sketch, &SvgOptions::default(), path("sketch.svg")).unwrap();
+        assert!(std::fs::read_to_string(path("sketch.svg")).unwrap().starts_with("<svg"));
+        raster::render_sketch(&sketch, &RasterOptions::default()).save_png(path("sketch.png")).unwrap();
+        assert!(std::fs::read(path("sketch.png")).unwrap().starts_with(b"\x89PNG"));
+
+        for name in ["sketches.jsonl.gz", "sketches.jsonl.zst"] {
+            jsonl_serializer::dump_sketches(std::slice::from_ref(&sketch), path(name)).unwrap();
+            let mut writer = jsonl_serializer::append_sketches(path(name)).unwrap();
+            writer.write_sketch(&sketch).unwrap();
+            drop(writer);
+            assert_eq!(jsonl_serializer::load_sketches(path(name)).unwrap().len(), 2);
+        }
+
+        compression::write(path("plain.txt"), "plain").unwrap();
+        assert_eq!(compression::read_to_string(path("plain.txt")).unwrap(), "plain");
+    }
+
+    #[test]
+    fn unipen_deserialization() {
+        let unipen = String::from(r#".VERSION 1.0
+.DATA_SOURCE "test corpus"
+.X_POINTS_PER_INCH 1000
+.Y_POINTS_PER_INCH 1000
+.POINTS_PER_SECOND 100
+.COORD X Y
+.HIERARCHY WORD CHARACTER
+.COMMENT recorded
+  on a tablet
+.SEGMENT WORD 0-2 OK "hi"
+.SEGMENT CHARACTER 0 ? "h"
+.segment character 2:0-2:1 BAD i
+.pen_down
+ 10 20
+ 11 21
+ 12 22
+.PEN_UP
+ 12 22
+.DT 50
+.PEN_DOWN
+ 30 40 31 41
+ .5 .7
+.PEN_UP
+"#);
+        let sketch = unipen_serializer::loads_sketch(unipen).unwrap();
+        assert_eq!(sketch.len(), 2);
+        assert_eq!(sketch.strokes[0].x, vec![10., 11., 12.]);
+        assert_eq!(sketch.strokes[1].y, vec![40., 41., 0.7]);
+        assert_eq!(sketch.strokes[0].timestamp, vec![0, 10, 20]);
+        assert_eq!(sketch.strokes[1].timestamp, vec![90, 100, 110]);
+        assert_eq!(sketch.meta.get("data_source"), Some(&json!("test corpus")));
+        assert_eq!(sketch.meta.get("x_points_per_inch"), Some(&json!(1000.)));
+        assert_eq!(sketch.coordinate_system.resolution, Some(1000.));
+
+        assert_eq!(sketch.groups.len(), 3);
+        assert_eq!(sketch.groups[0].name, "word");
+        assert_eq!(sketch.groups[0].label.as_deref(), Some("hi"));
+        assert_eq!(sketch.groups[0].stroke_indices(), vec![0, 1]);
+        assert_eq!(sketch.groups[1].attributes.get("quality"), Some(&json!("?")));
+        assert_eq!(sketch.groups[2].label.as_deref(), Some("i"));
+        assert_eq!(sketch.groups[2].strokes[0].points, Some((0, 2)));
+
+        let timed = String::from(".COORD X Y T P\n.PEN_DOWN\n1 2 1000 0.5\n3 4 1010 0.7\n");
+        let sketch = unipen_serializer::loads_sketch(timed).unwrap();
+        assert_eq!(sketch.strokes[0].timestamp, vec![1000, 1010]);
+        assert_eq!(sketch.strokes[0].pressure, vec![0.5, 0.7]);
+
+        assert!(matches!(unipen_serializer::loads_sketch(String::from(".PEN_DOWN\n1 2 3\n")), Err(InkError::Schema(_))));
+        assert!(matches!(unipen_serializer::loads_sketch(String::from(".SEGMENT WORD 0-4\n.PEN_DOWN\n1 2\n")), Err(InkError::Schema(_))));
+        let pen_up_first = String::from(".SEGMENT CHARACTER 1 ? \"a\"\n.PEN_UP\n0 0\n.PEN_DOWN\n1 2\n");
+        assert_eq!(unipen_serializer::loads_sketch(pen_up_first).unwrap().groups[0].stroke_indices(), vec![0]);
+    }
+
+    #[test]
+    fn iam_deserialization() {
+        let xml = r#"<?xml version="1.0" encoding="ISO-8859-1"?>
+<WhiteboardCaptureSession>
+  <General>
+    <Form id="a01-000u" writerID="10000"/>
+    <CaptureTime startTime="769.05"/>
+  </General>
+  <WhiteboardDescription>
+    <SensorLocation corner="top_left"/>
+    <DiagonallyOppositeCoords x="6512" y="1376"/>
+  </WhiteboardDescription>
+  <StrokeSet>
+    <Stroke colour="black" start_time="769.05" end_time="769.08">
+      <Point x="1073" y="1058" time="769.05"/>
+      <Point x="1072" y="1085" time="769.0625"/>
+    </Stroke>
+    <Stroke colour="black" start_time="769.70" end_time="769.70">
+      <Point x="1100" y="1050" time="769.70"/>
+    </Stroke>
+  </StrokeSet>
+  <Transcription>
+    <TextLine id="a01-000u-01" text="A MOVE to stop"/>
+    <TextLine id="a01-000u-02" text="Caf&#233; Gaitskell"/>
+  </Transcription>
+</WhiteboardCaptureSession>"#;
+        let sketch = iam_serializer::loads_sketch(String::from(xml)).unwrap();
+        assert_eq!(sketch.len(), 2);
+        assert_eq!(sketch.strokes[0].x, vec![1073., 1072.]);
+        assert_eq!(sketch.strokes[0].timestamp, vec![769050, 769063]);
+        assert_eq!(sketch.strokes[1].style.color.as_deref(), Some("black"));
+        assert_eq!(sketch.meta.get("form_id"), Some(&json!("a01-000u")));
+        assert_eq!(sketch.meta.get("writer_id"), Some(&json!("10000")));
+        assert_eq!(sketch.meta.get("capture_time_start_time"), Some(&json!("769.05")));
+        assert_eq!(sketch.meta["whiteboard"]["diagonally_opposite_coords"]["x"], json!(6512.));
+        assert_eq!(sketch.meta.get("transcription"), Some(&json!("A MOVE to stop\nCaf\u{e9} Gaitskell")));
+
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("a01-000u-01.xml");
+        let latin1: Vec<u8> = xml.replace("&#233;", "\u{e9}").chars().map(|c| c as u8).collect();
+        std::fs::write(&file_path, latin1).unwrap();
+        assert_eq!(iam_serializer::load_sketch(&file_path).unwrap(), sketch);
+
+        assert!(matches!(iam_serializer::loads_sketch(String::from("<ink/>")), Err(InkError::Schema(_))));
+        let missing = "<WhiteboardCaptureSession><StrokeSet><Stroke><Point y=\"1\"/></Stroke></StrokeSet></WhiteboardCaptureSession>";
+        assert!(matches!(iam_serializer::loads_sketch(String::from(missing)), Err(InkError::Schema(_))));
+    }
+
+    #[test]
+    fn quickdraw_serialization() {
+        let raw = r#"{"key_id":"5891796615823360","word":"nose","countrycode":"AE","timestamp":"2017-03-01 20:41:36.70725 UTC","recognized":true,"drawing":[[[129,128,129],[43,44,45],[0,18,31]],[[100,300],[10,110],[40,80]]]}"#;
+        let simplified = r#"{"word":"cat","countrycode":"US","recognized":false,"key_id":"1","drawing":[[[0,255],[0,100]]]}"#;
+
+        let sketch = quickdraw_serializer::loads_sketch(String::from(raw)).unwrap();
+        assert_eq!(sketch.len(), 2);
+        assert_eq!(sketch.strokes[0].x, vec![129., 128., 129.]);
+        assert_eq!(sketch.strokes[1].timestamp, vec![40, 80]);
+        assert_eq!(sketch.meta.get("word"), Some(&json!("nose")));
+        assert_eq!(sketch.meta.get("recognized"), Some(&json!(true)));
+        assert_eq!(quickdraw_serializer::dumps_sketch(&sketch, QuickDrawFormat::Raw), raw);
+
+        let converted = quickdraw_serializer::dumps_sketch(&sketch, QuickDrawFormat::Simplified);
+        let converted = quickdraw_serializer::loads_sketch(converted).unwrap();
+        assert!(converted.strokes.iter().all(|s| s.timestamp.is_empty()));
+        assert_eq!((converted.x_min(), converted.x_max(), converted.y_min()), (0., 255., 0.));
+        assert_eq!(converted.strokes[1].len(), 2);
+
+        let lines = format!("{}\n\n{}\n{{\"word\":\"dog\"}}\n{{\"drawing\":[[[1]]]}}\n", raw, simplified);
+        let mut reader = QuickDrawReader::new(lines.as_bytes());
+        assert_eq!(reader.next().unwrap().unwrap(), sketch);
+        assert!(reader.next().unwrap().unwrap().strokes[0].timestamp.is_empty());
+        assert!(matches!(reader.next().unwrap(), Err(InkError::Schema(message)) if message.starts_with("line 4: missing field `drawing`")));
+        assert!(matches!(reader.next().unwrap(), Err(InkError::Schema(message)) if message == "line 5: stroke 0 has no x and y arrays"));
+        assert!(reader.next().is_none());
+
+        let misaligned = String::from(r#"{"drawing":[[[1,2],[3,4]],[[1,2,3],[4,5]]]}"#);
+        assert!(matches!(quickdraw_serializer::loads_sketch(misaligned), Err(InkError::Schema(message)) if message == "stroke 1 has 3 x, 2 y and 0 t values"));
+        let untimed = String::from(r#"{"drawing":[[[1,2],[3,4],[0]]]}"#);
+        assert!(matches!(quickdraw_serializer::loads_sketch(untimed), Err(InkError::Schema(_))));
+
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("nose.ndjson.gz");
+        let sketches = quickdraw_serializer::loads_sketches(format!("{}\n{}\n", raw, simplified)).unwrap();
+        quickdraw_serializer::dump_sketches(&sketches, &file_path, QuickDrawFormat::Raw).unwrap();
+        assert_eq!(quickdraw_serializer::load_sketches(&file_path).unwrap(), sketches);
+        assert_eq!(quickdraw_serializer::dumps_sketches(&sketches, QuickDrawFormat::Raw).lines().count(), 2);
+    }
+
+    #[test]
+    fn stroke_sequences() {
+        let mut first = Stroke::new(vec![10., 12., 15.], vec![5., 5., 9.], vec![100, 110, 130], vec![0.5, 0.75, 1.]);
+        first.meta.insert(String::from("id"), json!(1));
+        let second = Stroke::new(vec![20., 21.], vec![0., 1.], vec![200, 210], vec![0.25, 0.5]);
+        let sketch = Sketch::new(vec![first, Stroke::new(vec![], vec![], vec![], vec![]), second]);
+
+        let stroke3 = sequence::to_stroke3(&sketch, &SequenceOptions::default());
+        assert_eq!(stroke3, vec![
+            vec![10., 5., 0.], vec![2., 0., 0.], vec![3., 4., 1.],
+            vec![5., -9., 0.], vec![1., 1., 1.],
+        ]);
+        let decoded = sequence::from_stroke3(&stroke3, &SequenceOptions::default());
+        assert_eq!(decoded.len(), 2);
+        assert_eq!(decoded.strokes[0].x, sketch.strokes[0].x);
+        assert_eq!(decoded.strokes[1].y, sketch.strokes[2].y);
+
+        let options = SequenceOptions { pressure: true, time: true, max_length: Some(7) };
+        let stroke5 = sequence::to_stroke5(&sketch, &options);
+        assert_eq!(stroke5.len(), 7);
+        assert_eq!(stroke5[0], vec![10., 5., 1., 0., 0., 0.5, 100.]);
+        assert_eq!(stroke5[3], vec![5., -9., 1., 0., 0., 0.25, 70.]);
+        assert_eq!(stroke5[4][..5], [1., 1., 0., 1., 0.]);
+        assert_eq!(stroke5[6], vec![0., 0., 0., 0., 1., 0., 0.]);
+        let decoded = sequence::from_stroke5(&stroke5, &options);
+        assert_eq!(decoded.strokes[0].timestamp, sketch.strokes[0].timestamp);
+        assert_eq!(decoded.strokes[1].pressure, sketch.strokes[2].pressure);
+        assert_eq!(decoded.strokes[1].x, sketch.strokes[2].x);
+
+        let padded = sequence::to_stroke3(&sketch, &SequenceOptions { max_length: Some(6), ..SequenceOptions::default() });
+        assert_eq!(padded[5], vec![0., 0., 0.]);
+        assert_eq!(sequence::from_stroke3(&padded, &SequenceOptions::default()).len(), 2);
+
+        let truncated = sequence::to_stroke5(&sketch, &SequenceOptions { max_length: Some(2), ..SequenceOptions::default() });
+        assert_eq!(truncated, vec![vec![10., 5., 1., 0., 0.], vec![2., 0., 0., 1., 0.]]);
+        assert_eq!(sequence::from_stroke5(&truncated, &SequenceOptions::default()).strokes[0].x, vec![10., 12.]);
+    }
+
+    #[test]
+    fn numpy_serialization() {
+        let first = Stroke::new(vec![1., 2., 3.], vec![4., 5., 6.], vec![10, 20, 30], vec![0.5, 0.75, 1.]);
+        let second = Stroke::new(vec![7., 8.], vec![9., 10.], vec![], vec![]);
+        let sketch = Sketch::new(vec![first.clone(), second.clone()]);
+        let other = Sketch::new(vec![Stroke::new(vec![0.], vec![1.], vec![5], vec![])]);
+
+        let bytes = numpy_serializer::dumps_sketch(&sketch).unwrap();
+        assert!(bytes.starts_with(b"\x93NUMPY\x01\x00"));
+        let header_length = u16::from_le_bytes([bytes[8], bytes[9]]) as usize;
+        assert_eq!((10 + header_length) % 64, 0);
+        assert!(std::str::from_utf8(&bytes[10..10 + header_length]).unwrap().contains("'shape': (5, 5)"));
+        let array = numpy_serializer::read_npy(&bytes).unwrap();
+        assert_eq!(array.shape, vec![5, 5]);
+        match array.data {
+            NpyData::Float(values) => {
+                assert_eq!(values[..5], [1., 4., 10., 0.5, 0.]);
+                assert!(values[17].is_nan() && values[18].is_nan());
+                assert_eq!(values[19], 1.);
+            }
+            NpyData::Integer(_) => panic!("points are stored as float"),
+        }
+        assert_eq!(numpy_serializer::loads_sketch(bytes).unwrap(), sketch);
+
+        let sketches = vec![sketch.clone(), other.clone()];
+        for layout in [NumpyLayout::Points, NumpyLayout::Padded] {
+            let bytes = numpy_serializer::dumps_sketches(&sketches, layout).unwrap();
+            let arrays = numpy_serializer::read_npz(bytes.clone()).unwrap();
+            let points = &arrays.iter().find(|(name, _)| name == "points").unwrap().1;
+            let lengths = &arrays.iter().find(|(name, _)| name == "lengths").unwrap().1;
+            assert_eq!(lengths.data, NpyData::Integer(vec![5, 1]));
+            match layout {
+                NumpyLayout::Points => assert_eq!(points.shape, vec![6, 5]),
+                NumpyLayout::Padded => assert_eq!(points.shape, vec![2, 5, 5]),
+            }
+            assert_eq!(numpy_serializer::loads_sketches(bytes).unwrap(), sketches);
+        }
+
+        let empty = Stroke::new(vec![], vec![], vec![], vec![]);
+        let gapped = vec![Sketch::new(vec![first.clone(), empty.clone(), second.clone()]), Sketch::new(vec![]), Sketch::new(vec![empty.clone()])];
+        for layout in [NumpyLayout::Points, NumpyLayout::Padded] {
+            let loaded = numpy_serializer::loads_sketches(numpy_serializer::dumps_sketches(&gapped, layout).unwrap()).unwrap();
+            assert_eq!(loaded, gapped);
+        }
+        assert!(matches!(numpy_serializer::dumps_sketch(&gapped[0]), Err(InkError::Schema(message)) if message.starts_with("stroke 1 has no points")));
+        let mismatched = NpyArray { shape: vec![2], data: NpyData::Integer(vec![1, 1]) };
+        let points = numpy_serializer::points(&sketch);
+        let lengths = NpyArray { shape: vec![1], data: NpyData::Integer(vec![5]) };
+        let strokes = NpyArray { shape: vec![1], data: NpyData::Integer(vec![2]) };
+        let archive = numpy_serializer::write_npz(&[("points", &points), ("lengths", &lengths), ("strokes", &strokes), ("stroke_lengths", &mismatched)]).unwrap();
+        assert!(matches!(numpy_serializer::loads_sketches(archive), Err(InkError::Schema(_))));
+        let archive = numpy_serializer::write_npz(&[("points", &points), ("lengths", &lengths)]).unwrap();
+        assert_eq!(numpy_serializer::loads_sketches(archive).unwrap(), vec![sketch.clone()]);
+
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("sketches.npz");
+        numpy_serializer::dump_sketches(&sketches, &path, NumpyLayout::Padded).unwrap();
+        assert_eq!(numpy_serializer::load_sketches(&path).unwrap(), sketches);
+
+        // little-endian int32 arrays as written by numpy without a stroke column
+        let mut bytes = b"\x93NUMPY\x01\x00".to_vec();
+        let header = format!("{{'descr': '<i4', 'fortran_order': False, 'shape': (2, 2), }}{:>53}", "\n");
+        bytes.extend_from_slice(&(header.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(header.as_bytes());
+        for value in [3i32, -4, 5, 6] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        let loaded = numpy_serializer::loads_sketch(bytes).unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded.strokes[0].y, vec![-4., 6.]);
+        assert!(loaded.strokes[0].timestamp.is_empty());
+
+        assert!(matches!(numpy_serializer::loads_sketch(b"not numpy".to_vec()), Err(InkError::Schema(_))));
+        assert!(matches!(numpy_serializer::loads_sketches(b"not a zip".to_vec()), Err(InkError::Schema(_))));
+
+        let crafted = |descr: &str, shape: &str| {
+            let header = format!("{{'descr': '{}', 'fortran_order': False, 'shape': {}, }}\n", descr, shape);
+            let mut bytes = b"\x93NUMPY\x01\x00".to_vec();
+            bytes.extend_from_slice(&(header.len() as u16).to_le_bytes());
+            bytes.extend_from_slice(header.as_bytes());
+            bytes.extend_from_slice(&[0; 16]);
+            bytes
+        };
+        for (descr, shape) in [("<f8", "(4294967296, 4294967296, 4294967296)"), ("<f8", "(2305843009213693952,)"), ("é8", "(1,)")] {
+            assert!(matches!(numpy_serializer::read_npy(&crafted(descr, shape)), Err(InkError::Schema(_))));
+        }
+        let points = NpyArray { shape: vec![0, 5], data: NpyData::Float(vec![]) };
+        let lengths = NpyArray { shape: vec![2], data: NpyData::Integer(vec![i64::MAX, i64::MAX]) };
+        let archive = numpy_serializer::write_npz(&[("points", &points), ("lengths", &lengths)]).unwrap();
+        assert!(matches!(numpy_serializer::loads_sketches(archive), Err(InkError::Schema(_))));
+    }
 }
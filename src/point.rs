@@ -0,0 +1,73 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+/// Single sample of a stroke, `timestamp` and `pressure` are `None` when the stroke does not record them
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+    pub timestamp: Option<u64>,
+    pub pressure: Option<f64>,
+}
+
+impl Point {
+    pub fn new(x: f64, y: f64, timestamp: Option<u64>, pressure: Option<f64>) -> Point {
+        Point { x, y, timestamp, pressure }
+    }
+}
+
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", json!(self))
+    }
+}
+
+/// Mutable view on a single sample of a stroke
+#[derive(Debug)]
+pub struct PointMut<'a> {
+    pub x: &'a mut f64,
+    pub y: &'a mut f64,
+    pub timestamp: Option<&'a mut u64>,
+    pub pressure: Option<&'a mut f64>,
+}
+
+/// Iterator returned by `Stroke::points_mut`
+pub struct PointsMut<'a> {
+    pub(crate) x: std::slice::IterMut<'a, f64>,
+    pub(crate) y: std::slice::IterMut<'a, f64>,
+    pub(crate) timestamp: Option<std::slice::IterMut<'a, u64>>,
+    pub(crate) pressure: Option<std::slice::IterMut<'a, f64>>,
+}
+
+impl<'a> Iterator for PointsMut<'a> {
+    type Item = PointMut<'a>;
+
+    fn next(&mut self) -> Option<PointMut<'a>> {
+        let x = self.x.next()?;
+        let y = self.y.next()?;
+        let timestamp = self.timestamp.as_mut().and_then(|t| t.next());
+        let pressure = self.pressure.as_mut().and_then(|p| p.next());
+        Some(PointMut { x, y, timestamp, pressure })
+    }
+}
+
+/// Appends a point to parallel channel vectors, optional values are only recorded while their channel is
+/// aligned with `x` and a missing value drops the channel, so that it never ends up partially filled
+pub(crate) fn push_point(x: &mut Vec<f64>, y: &mut Vec<f64>, timestamp: &mut Vec<u64>, pressure: &mut Vec<f64>, point: Point) {
+    if timestamp.len() == x.len() {
+        match point.timestamp {
+            Some(t) => timestamp.push(t),
+            None => timestamp.clear(),
+        }
+    }
+    if pressure.len() == x.len() {
+        match point.pressure {
+            Some(p) => pressure.push(p),
+            None => pressure.clear(),
+        }
+    }
+    x.push(point.x);
+    y.push(point.y);
+}
@@ -0,0 +1,250 @@
+use std::path::Path;
+
+use crate::error::InkError;
+use crate::serialization::compression;
+use crate::sketch::Sketch;
+use crate::stroke::Stroke;
+use crate::style;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PixelFormat {
+    Gray,
+    Rgba,
+}
+
+impl PixelFormat {
+    pub fn channels(&self) -> usize {
+        match self {
+            PixelFormat::Gray => 1,
+            PixelFormat::Rgba => 4,
+        }
+    }
+}
+
+/// Rendering options for rasterizing sketches
+#[derive(Clone, Debug)]
+pub struct RasterOptions {
+    pub width: u32,
+    pub height: u32,
+    pub format: PixelFormat,
+    /// Space around the sketch in pixels, only used when fitting to the canvas
+    pub margin: f64,
+    /// Line width in pixels, used for strokes without a width in their style
+    pub line_width: f64,
+    /// Modulate the line width by the pressure channel
+    pub pressure_sensitive: bool,
+    /// Smallest width factor applied to a line when rendering with pressure
+    pub min_width_factor: f64,
+    /// Scale and center the sketch into the canvas, otherwise coordinates are taken as pixels
+    pub fit_to_canvas: bool,
+    pub keep_aspect_ratio: bool,
+    /// RGBA color of strokes without a parsable color in their style, grayscale output uses its luminance
+    pub foreground: [u8; 4],
+    /// RGBA color of the background, grayscale output uses its luminance
+    pub background: [u8; 4],
+}
+
+impl Default for RasterOptions {
+    fn default() -> Self {
+        RasterOptions {
+            width: 256,
+            height: 256,
+            format: PixelFormat::Gray,
+            margin: 8.,
+            line_width: 2.,
+            pressure_sensitive: false,
+            min_width_factor: 0.2,
+            fit_to_canvas: true,
+            keep_aspect_ratio: true,
+            foreground: [0, 0, 0, 255],
+            background: [255, 255, 255, 255],
+        }
+    }
+}
+
+/// Pixel buffer holding a rendered sketch, rows are stored top to bottom
+#[derive(Clone, Debug, PartialEq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub format: PixelFormat,
+    pub pixels: Vec<u8>,
+}
+
+impl Image {
+    pub fn pixel(&self, x: u32, y: u32) -> &[u8] {
+        let channels = self.format.channels();
+        let start = (y as usize * self.width as usize + x as usize) * channels;
+        &self.pixels[start..start + channels]
+    }
+
+    /// Encodes the image as PNG
+    pub fn to_png(&self) -> Result<Vec<u8>, InkError> {
+        let mut data = vec![];
+        {
+            let mut encoder = png::Encoder::new(&mut data, self.width, self.height);
+            encoder.set_color(match self.format {
+                PixelFormat::Gray => png::ColorType::Grayscale,
+                PixelFormat::Rgba => png::ColorType::Rgba,
+            });
+            encoder.set_depth(png::BitDepth::Eight);
+            let mut writer = encoder.write_header()?;
+            writer.write_image_data(&self.pixels)?;
+        }
+        Ok(data)
+    }
+
+    pub fn save_png(&self, file_path: impl AsRef<Path>) -> Result<(), InkError> {
+        compression::write(file_path, self.to_png()?)
+    }
+}
+
+/// Renders a sketch into a pixel buffer with anti-aliased lines
+///
+/// Stroke styles are honored with their width taken as pixels. Strokes sharing color and opacity are drawn
+/// into one layer so overlaps do not darken, layers are blended in the order of their first stroke.
+pub fn render_sketch(sketch: &Sketch, options: &RasterOptions) -> Image {
+    let width = options.width as usize;
+    let height = options.height as usize;
+    let mut layers: Vec<([u8; 4], f32, Vec<f32>)> = vec![];
+
+    let fitted;
+    let sketch = if options.fit_to_canvas && sketch.strokes.iter().any(|s| !s.is_empty()) {
+        fitted = fit_to_canvas(sketch, options);
+        &fitted
+    } else {
+        sketch
+    };
+
+    let pressure_max = sketch.pressure_max();
+    for (i, stroke) in sketch.strokes.iter().enumerate().filter(|(_, s)| !s.is_empty()) {
+        let style = sketch.stroke_style(i);
+        let color = style.color.as_deref().and_then(style::parse_color).unwrap_or(options.foreground);
+        let opacity = style.opacity.unwrap_or(1.).clamp(0., 1.) as f32;
+        let line_width = style.width.unwrap_or(options.line_width);
+        let use_pressure = options.pressure_sensitive
+            && stroke.pressure.len() == stroke.len()
+            && pressure_max > 0.;
+        let radius = |i: usize| {
+            let factor = if use_pressure {
+                (stroke.pressure[i] / pressure_max).max(options.min_width_factor).min(1.)
+            } else {
+                1.
+            };
+            line_width * factor / 2.
+        };
+
+        let layer = match layers.iter().position(|l| l.0 == color && l.1 == opacity) {
+            Some(layer) => layer,
+            None => {
+                layers.push((color, opacity, vec![0f32; width * height]));
+                layers.len() - 1
+            }
+        };
+        draw_stroke(&mut layers[layer].2, width, height, stroke, &radius);
+    }
+
+    let background = convert_color(options.background, options.format);
+    let channels = options.format.channels();
+    let mut values: Vec<f32> = (0..width * height).flat_map(|_| background[..channels].iter().map(|&v| v as f32)).collect();
+    for (color, opacity, coverage) in layers.iter() {
+        let foreground = convert_color(*color, options.format);
+        for (pixel, &alpha) in values.chunks_mut(channels).zip(coverage.iter()) {
+            for c in 0..channels {
+                pixel[c] += (foreground[c] as f32 - pixel[c]) * alpha * opacity;
+            }
+        }
+    }
+    let pixels = values.iter().map(|v| v.round() as u8).collect();
+
+    Image { width: options.width, height: options.height, format: options.format, pixels }
+}
+
+/// Scales the sketch into the canvas using `Sketch::normalize` and centers it
+fn fit_to_canvas(sketch: &Sketch, options: &RasterOptions) -> Sketch {
+    let mut fitted = sketch.clone();
+    let available_width = (options.width as f64 - 2. * options.margin).max(1.);
+    let available_height = (options.height as f64 - 2. * options.margin).max(1.);
+
+    let sketch_width = fitted.x_max() - fitted.x_min();
+    let sketch_height = fitted.y_max() - fitted.y_min();
+    if sketch_width == 0. && sketch_height == 0. {
+        fitted.offset(Some(-fitted.x_min()), Some(-fitted.y_min()));
+    } else if options.keep_aspect_ratio || sketch_width == 0. || sketch_height == 0. {
+        let factor = (available_width / sketch_width).min(available_height / sketch_height);
+        fitted.normalize(factor * sketch_width.max(sketch_height), true);
+    } else {
+        fitted.normalize(1., false);
+        fitted.scale(Some(available_width), Some(available_height));
+    }
+
+    let x_offset = (options.width as f64 - fitted.x_max()) / 2.;
+    let y_offset = (options.height as f64 - fitted.y_max()) / 2.;
+    fitted.offset(Some(x_offset), Some(y_offset));
+    fitted
+}
+
+fn convert_color(color: [u8; 4], format: PixelFormat) -> [u8; 4] {
+    match format {
+        PixelFormat::Rgba => color,
+        PixelFormat::Gray => {
+            let luminance = 0.299 * color[0] as f64 + 0.587 * color[1] as f64 + 0.114 * color[2] as f64;
+            [luminance.round() as u8, 0, 0, 0]
+        }
+    }
+}
+
+fn draw_stroke(coverage: &mut [f32], width: usize, height: usize, stroke: &Stroke, radius: &dyn Fn(usize) -> f64) {
+    if stroke.len() == 1 {
+        draw_segment(coverage, width, height, (stroke.x[0], stroke.y[0], radius(0)), (stroke.x[0], stroke.y[0], radius(0)));
+        return;
+    }
+
+    for i in 1..stroke.len() {
+        let start = (stroke.x[i - 1], stroke.y[i - 1], radius(i - 1));
+        let end = (stroke.x[i], stroke.y[i], radius(i));
+        draw_segment(coverage, width, height, start, end);
+    }
+}
+
+/// Draws a capsule between two points whose radius is interpolated along the segment
+///
+/// Coverage is estimated from the distance of the pixel center to the segment, overlapping segments keep
+/// the maximum coverage so joints do not darken.
+fn draw_segment(coverage: &mut [f32], width: usize, height: usize, start: (f64, f64, f64), end: (f64, f64, f64)) {
+    let (x0, y0, r0) = start;
+    let (x1, y1, r1) = end;
+    let extent = r0.max(r1) + 1.;
+
+    let column_min = ((x0.min(x1) - extent).floor().max(0.)) as usize;
+    let column_max = ((x0.max(x1) + extent).ceil().min(width as f64 - 1.)).max(-1.);
+    let row_min = ((y0.min(y1) - extent).floor().max(0.)) as usize;
+    let row_max = ((y0.max(y1) + extent).ceil().min(height as f64 - 1.)).max(-1.);
+    if column_max < 0. || row_max < 0. {
+        return;
+    }
+
+    let dx = x1 - x0;
+    let dy = y1 - y0;
+    let length_squared = dx * dx + dy * dy;
+
+    for row in row_min..=(row_max as usize) {
+        for column in column_min..=(column_max as usize) {
+            let px = column as f64 + 0.5;
+            let py = row as f64 + 0.5;
+            let t = if length_squared > 0. {
+                (((px - x0) * dx + (py - y0) * dy) / length_squared).clamp(0., 1.)
+            } else {
+                0.
+            };
+            let distance = ((px - x0 - t * dx).powi(2) + (py - y0 - t * dy).powi(2)).sqrt();
+            let r = r0 + (r1 - r0) * t;
+            let alpha = (r + 0.5 - distance).clamp(0., 1.) as f32;
+
+            let index = row * width + column;
+            if alpha > coverage[index] {
+                coverage[index] = alpha;
+            }
+        }
+    }
+}
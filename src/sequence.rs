@@ -0,0 +1,165 @@
+//! Offset sequences of sketches as used by Sketch-RNN style sequence models
+//!
+//! Every point becomes a row `(dx, dy, pen…)` holding the offset from the previous point, the first row holds
+//! the offset from the origin. Stroke-3 rows have one pen column which is 1 on the last point of a stroke.
+//! Stroke-5 rows have the one-hot pen states `(p1, p2, p3)`: pen stays down, pen is lifted after this point,
+//! and end of the sketch, the latter only used on padding rows.
+
+use crate::sketch::Sketch;
+use crate::stroke::Stroke;
+
+/// Columns and length of the sequence matrices
+#[derive(Clone, Debug, Default)]
+pub struct SequenceOptions {
+    /// Append the pressure of each point as a column, 0 for strokes without pressure
+    pub pressure: bool,
+    /// Append the time since the previous point as a column, 0 for strokes without timestamps
+    pub time: bool,
+    /// Pad or truncate the sequence to exactly this many rows, truncated sequences end with a pen lift
+    pub max_length: Option<usize>,
+}
+
+impl SequenceOptions {
+    /// Number of columns of a stroke-3 row
+    pub fn stroke3_columns(&self) -> usize {
+        3 + self.pressure as usize + self.time as usize
+    }
+
+    /// Number of columns of a stroke-5 row
+    pub fn stroke5_columns(&self) -> usize {
+        5 + self.pressure as usize + self.time as usize
+    }
+}
+
+/// Offsets, pen lift flag, pressure and time delta of every point of the non-empty strokes
+fn offsets(sketch: &Sketch) -> Vec<(f64, f64, bool, f64, f64)> {
+    let mut rows = vec![];
+    let (mut x, mut y, mut t) = (0., 0., 0.);
+    for stroke in sketch.strokes.iter().filter(|s| !s.is_empty()) {
+        let has_pressure = stroke.pressure.len() == stroke.len();
+        let has_time = stroke.timestamp.len() == stroke.len();
+        for i in 0..stroke.len() {
+            let pressure = if has_pressure { stroke.pressure[i] } else { 0. };
+            let dt = if has_time {
+                let dt = stroke.timestamp[i] as f64 - t;
+                t = stroke.timestamp[i] as f64;
+                dt
+            } else {
+                0.
+            };
+            rows.push((stroke.x[i] - x, stroke.y[i] - y, i == stroke.len() - 1, pressure, dt));
+            x = stroke.x[i];
+            y = stroke.y[i];
+        }
+    }
+    rows
+}
+
+/// Cuts rows beyond `max_length`, making sure the last kept point lifts the pen
+fn truncate(rows: &mut Vec<(f64, f64, bool, f64, f64)>, max_length: Option<usize>) {
+    if let Some(max_length) = max_length {
+        if rows.len() > max_length {
+            rows.truncate(max_length);
+            if let Some(last) = rows.last_mut() {
+                last.2 = true;
+            }
+        }
+    }
+}
+
+fn extra_columns(row: &mut Vec<f64>, pressure: f64, dt: f64, options: &SequenceOptions) {
+    if options.pressure {
+        row.push(pressure);
+    }
+    if options.time {
+        row.push(dt);
+    }
+}
+
+/// Encodes a sketch as stroke-3 rows `(dx, dy, pen_lift[, pressure][, dt])`, padding rows are all zero
+pub fn to_stroke3(sketch: &Sketch, options: &SequenceOptions) -> Vec<Vec<f64>> {
+    let mut offsets = offsets(sketch);
+    truncate(&mut offsets, options.max_length);
+
+    let mut rows: Vec<Vec<f64>> = offsets.into_iter()
+        .map(|(dx, dy, lift, pressure, dt)| {
+            let mut row = vec![dx, dy, if lift { 1. } else { 0. }];
+            extra_columns(&mut row, pressure, dt, options);
+            row
+        })
+        .collect();
+    if let Some(max_length) = options.max_length {
+        rows.resize(max_length, vec![0.; options.stroke3_columns()]);
+    }
+    rows
+}
+
+/// Encodes a sketch as stroke-5 rows `(dx, dy, p1, p2, p3[, pressure][, dt])`, padding rows are `(0, 0, 0, 0, 1)`
+pub fn to_stroke5(sketch: &Sketch, options: &SequenceOptions) -> Vec<Vec<f64>> {
+    let mut offsets = offsets(sketch);
+    truncate(&mut offsets, options.max_length);
+
+    let mut rows: Vec<Vec<f64>> = offsets.into_iter()
+        .map(|(dx, dy, lift, pressure, dt)| {
+            let mut row = if lift { vec![dx, dy, 0., 1., 0.] } else { vec![dx, dy, 1., 0., 0.] };
+            extra_columns(&mut row, pressure, dt, options);
+            row
+        })
+        .collect();
+    if let Some(max_length) = options.max_length {
+        let mut padding = vec![0.; options.stroke5_columns()];
+        padding[4] = 1.;
+        rows.resize(max_length, padding);
+    }
+    rows
+}
+
+/// Rebuilds absolute coordinates from offset rows, each paired with whether it ends its stroke
+fn reconstruct<'a>(rows: impl Iterator<Item = (&'a [f64], bool)>, first_extra: usize, options: &SequenceOptions) -> Sketch {
+    let mut sketch = Sketch::new(vec![]);
+    let mut stroke = Stroke::new(vec![], vec![], vec![], vec![]);
+    let (mut x, mut y, mut t) = (0., 0., 0.);
+    for (row, pen_lift) in rows {
+        x += row[0];
+        y += row[1];
+        stroke.x.push(x);
+        stroke.y.push(y);
+
+        let mut column = first_extra;
+        if options.pressure {
+            stroke.pressure.push(row.get(column).copied().unwrap_or(0.));
+            column += 1;
+        }
+        if options.time {
+            t += row.get(column).copied().unwrap_or(0.);
+            stroke.timestamp.push(t.max(0.).round() as u64);
+        }
+
+        if pen_lift {
+            sketch.add_stroke(std::mem::replace(&mut stroke, Stroke::new(vec![], vec![], vec![], vec![])));
+        }
+    }
+    if !stroke.is_empty() {
+        sketch.add_stroke(stroke);
+    }
+    sketch
+}
+
+/// Decodes stroke-3 rows, all-zero rows after the last pen lift are taken as padding
+pub fn from_stroke3(rows: &[Vec<f64>], options: &SequenceOptions) -> Sketch {
+    let end = rows.iter().rposition(|row| row.iter().any(|&v| v != 0.)).map_or(0, |i| i + 1);
+
+    let rows = rows[..end].iter()
+        .filter(|row| row.len() >= 3)
+        .map(|row| (row.as_slice(), row[2] > 0.5));
+    reconstruct(rows, 3, options)
+}
+
+/// Decodes stroke-5 rows up to the first end-of-sketch row
+pub fn from_stroke5(rows: &[Vec<f64>], options: &SequenceOptions) -> Sketch {
+    let rows = rows.iter()
+        .filter(|row| row.len() >= 5)
+        .take_while(|row| row[4] < 0.5)
+        .map(|row| (row.as_slice(), row[3] > 0.5));
+    reconstruct(rows, 5, options)
+}
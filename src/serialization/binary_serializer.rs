@@ -0,0 +1,706 @@
+//! Compact binary format for strokes and sketches
+//!
+//! A file starts with the magic bytes `DINK`, the format version and the kind of the stored object. Numbers are
+//! LEB128 varints, signed values are zigzag encoded. Every stroke stores its channels as delta-encoded integers:
+//! integer-valued float channels and timestamps are stored losslessly, other float channels are either quantized
+//! to a fixed step or kept as raw `f64`. Everything besides the point data (type, meta, extra channels, style,
+//! groups and coordinate system) is stored as compact JSON, and omitted when it holds the defaults. Documents
+//! store every page as its dimensions followed by its sketch.
+
+use std::io::{Read, Write};
+use std::path::Path;
+
+use serde_json::{Map, Value};
+
+use crate::document::{Document, Page};
+use crate::error::InkError;
+use crate::serialization::compression;
+use crate::serialization::{check_document, check_sketch, check_sketch_at, check_stroke, LoadOptions};
+use crate::sketch::Sketch;
+use crate::stroke::Stroke;
+use crate::units::Unit;
+
+const MAGIC: &[u8; 4] = b"DINK";
+pub const VERSION: u8 = 1;
+
+const KIND_STROKE: u8 = 1;
+const KIND_SKETCH: u8 = 2;
+const KIND_SKETCHES: u8 = 3;
+const KIND_STROKES: u8 = 4;
+const KIND_DOCUMENT: u8 = 5;
+
+const ENCODING_INTEGER: u8 = 0;
+const ENCODING_QUANTIZED: u8 = 1;
+const ENCODING_RAW: u8 = 2;
+
+/// Largest magnitude for which every integer is exactly representable as `f64`
+const MAX_EXACT_INTEGER: f64 = 9007199254740992.;
+
+/// Quantization of float channels that do not hold integers only, the default stores everything without loss
+#[derive(Clone, Debug, PartialEq)]
+pub struct BinaryOptions {
+    /// Step x/y coordinates are rounded to, `None` stores them as raw `f64`
+    pub coordinate_step: Option<f64>,
+    /// Step pressure values are rounded to, `None` stores them as raw `f64`
+    pub pressure_step: Option<f64>,
+}
+
+impl BinaryOptions {
+    /// Stores all float channels without loss
+    pub fn lossless() -> BinaryOptions {
+        BinaryOptions { coordinate_step: None, pressure_step: None }
+    }
+
+    /// Lossless coordinates and pressure quantized to 16 bit precision for the usual range of 0 to 1
+    pub fn quantized() -> BinaryOptions {
+        BinaryOptions { coordinate_step: None, pressure_step: Some(1. / 65536.) }
+    }
+}
+
+impl Default for BinaryOptions {
+    fn default() -> Self {
+        BinaryOptions::lossless()
+    }
+}
+
+pub fn dump_stroke(stroke: &Stroke, file_path: impl AsRef<Path>) -> Result<(), InkError> {
+    dump_stroke_with_options(stroke, file_path, &BinaryOptions::default())
+}
+
+pub fn dump_strokes(strokes: &[Stroke], file_path: impl AsRef<Path>) -> Result<(), InkError> {
+    dump_strokes_with_options(strokes, file_path, &BinaryOptions::default())
+}
+
+pub fn dump_sketch(sketch: &Sketch, file_path: impl AsRef<Path>) -> Result<(), InkError> {
+    dump_sketch_with_options(sketch, file_path, &BinaryOptions::default())
+}
+
+pub fn dump_sketches(sketches: &[Sketch], file_path: impl AsRef<Path>) -> Result<(), InkError> {
+    dump_sketches_with_options(sketches, file_path, &BinaryOptions::default())
+}
+
+pub fn dump_document(document: &Document, file_path: impl AsRef<Path>) -> Result<(), InkError> {
+    dump_document_with_options(document, file_path, &BinaryOptions::default())
+}
+
+pub fn dump_stroke_with_options(stroke: &Stroke, file_path: impl AsRef<Path>, options: &BinaryOptions) -> Result<(), InkError> {
+    compression::write(file_path, dumps_stroke_with_options(stroke, options))
+}
+
+pub fn dump_strokes_with_options(strokes: &[Stroke], file_path: impl AsRef<Path>, options: &BinaryOptions) -> Result<(), InkError> {
+    compression::write(file_path, dumps_strokes_with_options(strokes, options))
+}
+
+pub fn dump_sketch_with_options(sketch: &Sketch, file_path: impl AsRef<Path>, options: &BinaryOptions) -> Result<(), InkError> {
+    compression::write(file_path, dumps_sketch_with_options(sketch, options))
+}
+
+pub fn dump_sketches_with_options(sketches: &[Sketch], file_path: impl AsRef<Path>, options: &BinaryOptions) -> Result<(), InkError> {
+    compression::write(file_path, dumps_sketches_with_options(sketches, options))
+}
+
+pub fn dump_document_with_options(document: &Document, file_path: impl AsRef<Path>, options: &BinaryOptions) -> Result<(), InkError> {
+    compression::write(file_path, dumps_document_with_options(document, options))
+}
+
+pub fn dumps_stroke(stroke: &Stroke) -> Vec<u8> {
+    dumps_stroke_with_options(stroke, &BinaryOptions::default())
+}
+
+pub fn dumps_strokes(strokes: &[Stroke]) -> Vec<u8> {
+    dumps_strokes_with_options(strokes, &BinaryOptions::default())
+}
+
+pub fn dumps_sketch(sketch: &Sketch) -> Vec<u8> {
+    dumps_sketch_with_options(sketch, &BinaryOptions::default())
+}
+
+pub fn dumps_sketches(sketches: &[Sketch]) -> Vec<u8> {
+    dumps_sketches_with_options(sketches, &BinaryOptions::default())
+}
+
+pub fn dumps_document(document: &Document) -> Vec<u8> {
+    dumps_document_with_options(document, &BinaryOptions::default())
+}
+
+pub fn dumps_stroke_with_options(stroke: &Stroke, options: &BinaryOptions) -> Vec<u8> {
+    let mut out = header(KIND_STROKE);
+    write_stroke_body(&mut out, stroke, options);
+    out
+}
+
+pub fn dumps_strokes_with_options(strokes: &[Stroke], options: &BinaryOptions) -> Vec<u8> {
+    let mut out = header(KIND_STROKES);
+    write_varint(&mut out, strokes.len() as u64);
+    for stroke in strokes.iter() {
+        write_stroke_body(&mut out, stroke, options);
+    }
+    out
+}
+
+pub fn dumps_sketch_with_options(sketch: &Sketch, options: &BinaryOptions) -> Vec<u8> {
+    let mut out = header(KIND_SKETCH);
+    write_sketch_body(&mut out, sketch, options);
+    out
+}
+
+pub fn dumps_sketches_with_options(sketches: &[Sketch], options: &BinaryOptions) -> Vec<u8> {
+    let mut out = header(KIND_SKETCHES);
+    write_varint(&mut out, sketches.len() as u64);
+    for sketch in sketches.iter() {
+        write_sketch_body(&mut out, sketch, options);
+    }
+    out
+}
+
+pub fn dumps_document_with_options(document: &Document, options: &BinaryOptions) -> Vec<u8> {
+    let mut out = header(KIND_DOCUMENT);
+    write_document_body(&mut out, document, options);
+    out
+}
+
+pub fn write_stroke(stroke: &Stroke, mut writer: impl Write, options: &BinaryOptions) -> Result<(), InkError> {
+    writer.write_all(&dumps_stroke_with_options(stroke, options))?;
+    Ok(())
+}
+
+pub fn write_strokes(strokes: &[Stroke], mut writer: impl Write, options: &BinaryOptions) -> Result<(), InkError> {
+    writer.write_all(&dumps_strokes_with_options(strokes, options))?;
+    Ok(())
+}
+
+pub fn write_sketch(sketch: &Sketch, mut writer: impl Write, options: &BinaryOptions) -> Result<(), InkError> {
+    writer.write_all(&dumps_sketch_with_options(sketch, options))?;
+    Ok(())
+}
+
+pub fn write_sketches(sketches: &[Sketch], mut writer: impl Write, options: &BinaryOptions) -> Result<(), InkError> {
+    writer.write_all(&dumps_sketches_with_options(sketches, options))?;
+    Ok(())
+}
+
+pub fn write_document(document: &Document, mut writer: impl Write, options: &BinaryOptions) -> Result<(), InkError> {
+    writer.write_all(&dumps_document_with_options(document, options))?;
+    Ok(())
+}
+
+pub fn load_stroke(file_path: impl AsRef<Path>) -> Result<Stroke, InkError> {
+    load_stroke_with_options(file_path, &LoadOptions::default())
+}
+
+pub fn load_strokes(file_path: impl AsRef<Path>) -> Result<Vec<Stroke>, InkError> {
+    load_strokes_with_options(file_path, &LoadOptions::default())
+}
+
+pub fn load_sketch(file_path: impl AsRef<Path>) -> Result<Sketch, InkError> {
+    load_sketch_with_options(file_path, &LoadOptions::default())
+}
+
+pub fn load_sketches(file_path: impl AsRef<Path>) -> Result<Vec<Sketch>, InkError> {
+    load_sketches_with_options(file_path, &LoadOptions::default())
+}
+
+pub fn load_document(file_path: impl AsRef<Path>) -> Result<Document, InkError> {
+    load_document_with_options(file_path, &LoadOptions::default())
+}
+
+pub fn load_stroke_with_options(file_path: impl AsRef<Path>, options: &LoadOptions) -> Result<Stroke, InkError> {
+    loads_stroke_with_options(compression::read(file_path)?, options)
+}
+
+pub fn load_strokes_with_options(file_path: impl AsRef<Path>, options: &LoadOptions) -> Result<Vec<Stroke>, InkError> {
+    loads_strokes_with_options(compression::read(file_path)?, options)
+}
+
+pub fn load_sketch_with_options(file_path: impl AsRef<Path>, options: &LoadOptions) -> Result<Sketch, InkError> {
+    loads_sketch_with_options(compression::read(file_path)?, options)
+}
+
+pub fn load_sketches_with_options(file_path: impl AsRef<Path>, options: &LoadOptions) -> Result<Vec<Sketch>, InkError> {
+    loads_sketches_with_options(compression::read(file_path)?, options)
+}
+
+pub fn load_document_with_options(file_path: impl AsRef<Path>, options: &LoadOptions) -> Result<Document, InkError> {
+    loads_document_with_options(compression::read(file_path)?, options)
+}
+
+pub fn loads_stroke(serialized_bytes: Vec<u8>) -> Result<Stroke, InkError> {
+    loads_stroke_with_options(serialized_bytes, &LoadOptions::default())
+}
+
+pub fn loads_strokes(serialized_bytes: Vec<u8>) -> Result<Vec<Stroke>, InkError> {
+    loads_strokes_with_options(serialized_bytes, &LoadOptions::default())
+}
+
+pub fn loads_sketch(serialized_bytes: Vec<u8>) -> Result<Sketch, InkError> {
+    loads_sketch_with_options(serialized_bytes, &LoadOptions::default())
+}
+
+pub fn loads_sketches(serialized_bytes: Vec<u8>) -> Result<Vec<Sketch>, InkError> {
+    loads_sketches_with_options(serialized_bytes, &LoadOptions::default())
+}
+
+pub fn loads_document(serialized_bytes: Vec<u8>) -> Result<Document, InkError> {
+    loads_document_with_options(serialized_bytes, &LoadOptions::default())
+}
+
+pub fn loads_stroke_with_options(serialized_bytes: Vec<u8>, options: &LoadOptions) -> Result<Stroke, InkError> {
+    let mut reader = ByteReader::new(&serialized_bytes);
+    reader.read_header(KIND_STROKE)?;
+    let stroke = reader.read_stroke()?;
+    reader.finish()?;
+    check_stroke(&stroke, 0, options)?;
+    Ok(stroke)
+}
+
+pub fn loads_strokes_with_options(serialized_bytes: Vec<u8>, options: &LoadOptions) -> Result<Vec<Stroke>, InkError> {
+    let mut reader = ByteReader::new(&serialized_bytes);
+    reader.read_header(KIND_STROKES)?;
+    let count = reader.read_length()?;
+    let mut strokes = Vec::with_capacity(count);
+    for index in 0..count {
+        let stroke = reader.read_stroke()?;
+        check_stroke(&stroke, index, options)?;
+        strokes.push(stroke);
+    }
+    reader.finish()?;
+    Ok(strokes)
+}
+
+pub fn loads_sketch_with_options(serialized_bytes: Vec<u8>, options: &LoadOptions) -> Result<Sketch, InkError> {
+    let mut reader = ByteReader::new(&serialized_bytes);
+    reader.read_header(KIND_SKETCH)?;
+    let sketch = reader.read_sketch()?;
+    reader.finish()?;
+    check_sketch(&sketch, options)?;
+    Ok(sketch)
+}
+
+pub fn loads_sketches_with_options(serialized_bytes: Vec<u8>, options: &LoadOptions) -> Result<Vec<Sketch>, InkError> {
+    let mut reader = ByteReader::new(&serialized_bytes);
+    reader.read_header(KIND_SKETCHES)?;
+    let count = reader.read_length()?;
+    let mut sketches = Vec::with_capacity(count);
+    for index in 0..count {
+        let sketch = reader.read_sketch()?;
+        check_sketch_at(&sketch, index, options)?;
+        sketches.push(sketch);
+    }
+    reader.finish()?;
+    Ok(sketches)
+}
+
+pub fn loads_document_with_options(serialized_bytes: Vec<u8>, options: &LoadOptions) -> Result<Document, InkError> {
+    let mut reader = ByteReader::new(&serialized_bytes);
+    reader.read_header(KIND_DOCUMENT)?;
+    let document = reader.read_document()?;
+    reader.finish()?;
+    check_document(&document, options)?;
+    Ok(document)
+}
+
+pub fn read_stroke(reader: impl Read) -> Result<Stroke, InkError> {
+    read_stroke_with_options(reader, &LoadOptions::default())
+}
+
+pub fn read_strokes(reader: impl Read) -> Result<Vec<Stroke>, InkError> {
+    read_strokes_with_options(reader, &LoadOptions::default())
+}
+
+pub fn read_sketch(reader: impl Read) -> Result<Sketch, InkError> {
+    read_sketch_with_options(reader, &LoadOptions::default())
+}
+
+pub fn read_sketches(reader: impl Read) -> Result<Vec<Sketch>, InkError> {
+    read_sketches_with_options(reader, &LoadOptions::default())
+}
+
+pub fn read_document(reader: impl Read) -> Result<Document, InkError> {
+    read_document_with_options(reader, &LoadOptions::default())
+}
+
+pub fn read_stroke_with_options(mut reader: impl Read, options: &LoadOptions) -> Result<Stroke, InkError> {
+    let mut serialized_bytes = vec![];
+    reader.read_to_end(&mut serialized_bytes)?;
+    loads_stroke_with_options(serialized_bytes, options)
+}
+
+pub fn read_strokes_with_options(mut reader: impl Read, options: &LoadOptions) -> Result<Vec<Stroke>, InkError> {
+    let mut serialized_bytes = vec![];
+    reader.read_to_end(&mut serialized_bytes)?;
+    loads_strokes_with_options(serialized_bytes, options)
+}
+
+pub fn read_sketch_with_options(mut reader: impl Read, options: &LoadOptions) -> Result<Sketch, InkError> {
+    let mut serialized_bytes = vec![];
+    reader.read_to_end(&mut serialized_bytes)?;
+    loads_sketch_with_options(serialized_bytes, options)
+}
+
+pub fn read_sketches_with_options(mut reader: impl Read, options: &LoadOptions) -> Result<Vec<Sketch>, InkError> {
+    let mut serialized_bytes = vec![];
+    reader.read_to_end(&mut serialized_bytes)?;
+    loads_sketches_with_options(serialized_bytes, options)
+}
+
+pub fn read_document_with_options(mut reader: impl Read, options: &LoadOptions) -> Result<Document, InkError> {
+    let mut serialized_bytes = vec![];
+    reader.read_to_end(&mut serialized_bytes)?;
+    loads_document_with_options(serialized_bytes, options)
+}
+
+fn header(kind: u8) -> Vec<u8> {
+    let mut out = MAGIC.to_vec();
+    out.push(VERSION);
+    out.push(kind);
+    out
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn write_signed(out: &mut Vec<u8>, value: i64) {
+    write_varint(out, ((value << 1) ^ (value >> 63)) as u64);
+}
+
+fn is_integer(value: f64) -> bool {
+    value.fract() == 0. && value.abs() <= MAX_EXACT_INTEGER
+}
+
+fn write_floats(out: &mut Vec<u8>, values: &[f64], step: Option<f64>) {
+    write_varint(out, values.len() as u64);
+    let step = step.filter(|&s| s > 0. && s.is_finite());
+    let quantizable = |step: f64| values.iter().all(|v| (v / step).abs() <= MAX_EXACT_INTEGER);
+    let (encoding, step) = if values.iter().all(|&v| is_integer(v)) {
+        (ENCODING_INTEGER, 1.)
+    } else {
+        match step {
+            Some(step) if quantizable(step) => (ENCODING_QUANTIZED, step),
+            _ => (ENCODING_RAW, 0.),
+        }
+    };
+
+    out.push(encoding);
+    if encoding == ENCODING_RAW {
+        for value in values.iter() {
+            out.extend_from_slice(&value.to_le_bytes());
+        }
+        return;
+    }
+    if encoding == ENCODING_QUANTIZED {
+        out.extend_from_slice(&step.to_le_bytes());
+    }
+    let mut previous = 0i64;
+    for value in values.iter() {
+        let quantized = (value / step).round() as i64;
+        write_signed(out, quantized.wrapping_sub(previous));
+        previous = quantized;
+    }
+}
+
+fn write_timestamps(out: &mut Vec<u8>, values: &[u64]) {
+    write_varint(out, values.len() as u64);
+    let mut previous = 0u64;
+    for &value in values.iter() {
+        write_signed(out, value.wrapping_sub(previous) as i64);
+        previous = value;
+    }
+}
+
+/// Compact JSON of the non-default fields, an empty envelope is stored as zero length
+fn write_envelope(out: &mut Vec<u8>, envelope: Map<String, Value>) {
+    if envelope.is_empty() {
+        write_varint(out, 0);
+        return;
+    }
+    let json = Value::Object(envelope).to_string();
+    write_varint(out, json.len() as u64);
+    out.extend_from_slice(json.as_bytes());
+}
+
+fn write_stroke_body(out: &mut Vec<u8>, stroke: &Stroke, options: &BinaryOptions) {
+    write_floats(out, &stroke.x, options.coordinate_step);
+    write_floats(out, &stroke.y, options.coordinate_step);
+    write_timestamps(out, &stroke.timestamp);
+    write_floats(out, &stroke.pressure, options.pressure_step);
+
+    let mut envelope = Map::new();
+    if stroke.typ != "stroke" {
+        envelope.insert(String::from("type"), Value::from(stroke.typ.clone()));
+    }
+    if !stroke.meta.is_empty() {
+        envelope.insert(String::from("meta"), serde_json::to_value(&stroke.meta).unwrap());
+    }
+    if !stroke.channels.is_empty() {
+        envelope.insert(String::from("channels"), serde_json::to_value(&stroke.channels).unwrap());
+    }
+    if !stroke.style.is_empty() {
+        envelope.insert(String::from("style"), serde_json::to_value(&stroke.style).unwrap());
+    }
+    write_envelope(out, envelope);
+}
+
+fn write_sketch_body(out: &mut Vec<u8>, sketch: &Sketch, options: &BinaryOptions) {
+    write_varint(out, sketch.strokes.len() as u64);
+    for stroke in sketch.strokes.iter() {
+        write_stroke_body(out, stroke, options);
+    }
+
+    let mut envelope = Map::new();
+    if sketch.typ != "stroke" {
+        envelope.insert(String::from("type"), Value::from(sketch.typ.clone()));
+    }
+    if !sketch.meta.is_empty() {
+        envelope.insert(String::from("meta"), serde_json::to_value(&sketch.meta).unwrap());
+    }
+    if !sketch.groups.is_empty() {
+        envelope.insert(String::from("groups"), serde_json::to_value(&sketch.groups).unwrap());
+    }
+    if !sketch.style.is_empty() {
+        envelope.insert(String::from("style"), serde_json::to_value(&sketch.style).unwrap());
+    }
+    if !sketch.coordinate_system.is_default() {
+        envelope.insert(String::from("coordinate_system"), serde_json::to_value(sketch.coordinate_system).unwrap());
+    }
+    write_envelope(out, envelope);
+}
+
+/// Pages store their dimensions as raw `f64` followed by the sketch, unit and metadata go into the envelope
+fn write_document_body(out: &mut Vec<u8>, document: &Document, options: &BinaryOptions) {
+    write_varint(out, document.pages.len() as u64);
+    for page in document.pages.iter() {
+        out.extend_from_slice(&page.width.to_le_bytes());
+        out.extend_from_slice(&page.height.to_le_bytes());
+        write_sketch_body(out, &page.sketch, options);
+
+        let mut envelope = Map::new();
+        if page.typ != "page" {
+            envelope.insert(String::from("type"), Value::from(page.typ.clone()));
+        }
+        if !page.meta.is_empty() {
+            envelope.insert(String::from("meta"), serde_json::to_value(&page.meta).unwrap());
+        }
+        envelope.insert(String::from("unit"), serde_json::to_value(page.unit).unwrap());
+        write_envelope(out, envelope);
+    }
+
+    let mut envelope = Map::new();
+    if document.typ != "document" {
+        envelope.insert(String::from("type"), Value::from(document.typ.clone()));
+    }
+    if !document.meta.is_empty() {
+        envelope.insert(String::from("meta"), serde_json::to_value(&document.meta).unwrap());
+    }
+    write_envelope(out, envelope);
+}
+
+/// Cursor over the input, errors report the byte offset as column of line 1
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn new(bytes: &'a [u8]) -> ByteReader<'a> {
+        ByteReader { bytes, position: 0 }
+    }
+
+    fn error(&self, message: &str) -> InkError {
+        InkError::Parse { message: String::from(message), line: 1, column: self.position + 1 }
+    }
+
+    fn remaining(&self) -> usize {
+        self.bytes.len() - self.position
+    }
+
+    fn read_bytes(&mut self, count: usize) -> Result<&'a [u8], InkError> {
+        if count > self.remaining() {
+            return Err(self.error("unexpected end of input"));
+        }
+        let bytes = &self.bytes[self.position..self.position + count];
+        self.position += count;
+        Ok(bytes)
+    }
+
+    fn read_byte(&mut self) -> Result<u8, InkError> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    fn read_f64(&mut self) -> Result<f64, InkError> {
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(self.read_bytes(8)?);
+        Ok(f64::from_le_bytes(bytes))
+    }
+
+    fn read_varint(&mut self) -> Result<u64, InkError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.read_byte()?;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(self.error("varint is too long"))
+    }
+
+    fn read_signed(&mut self) -> Result<i64, InkError> {
+        let value = self.read_varint()?;
+        Ok((value >> 1) as i64 ^ -((value & 1) as i64))
+    }
+
+    /// Element count, checked against the remaining input so corrupt counts do not allocate huge buffers
+    fn read_length(&mut self) -> Result<usize, InkError> {
+        let length = self.read_varint()?;
+        if length > self.remaining() as u64 {
+            return Err(self.error("length exceeds the remaining input"));
+        }
+        Ok(length as usize)
+    }
+
+    fn read_header(&mut self, kind: u8) -> Result<(), InkError> {
+        if self.read_bytes(MAGIC.len()).ok() != Some(&MAGIC[..]) {
+            return Err(InkError::Schema(String::from("input is not a binary ink file")));
+        }
+        let version = self.read_byte()?;
+        if version != VERSION {
+            return Err(InkError::Schema(format!("unsupported binary format version {}, expected {}", version, VERSION)));
+        }
+        let found = self.read_byte()?;
+        if found != kind {
+            return Err(InkError::Schema(format!("expected {}, found {}", kind_name(kind), kind_name(found))));
+        }
+        Ok(())
+    }
+
+    fn finish(&self) -> Result<(), InkError> {
+        if self.remaining() > 0 {
+            return Err(self.error("trailing bytes after the ink object"));
+        }
+        Ok(())
+    }
+
+    fn read_floats(&mut self) -> Result<Vec<f64>, InkError> {
+        let count = self.read_length()?;
+        let encoding = self.read_byte()?;
+        let step = match encoding {
+            ENCODING_INTEGER => 1.,
+            ENCODING_QUANTIZED => self.read_f64()?,
+            ENCODING_RAW => return (0..count).map(|_| self.read_f64()).collect(),
+            _ => return Err(self.error("unknown channel encoding")),
+        };
+
+        let mut values = Vec::with_capacity(count);
+        let mut previous = 0i64;
+        for _ in 0..count {
+            previous = previous.wrapping_add(self.read_signed()?);
+            values.push(previous as f64 * step);
+        }
+        Ok(values)
+    }
+
+    fn read_timestamps(&mut self) -> Result<Vec<u64>, InkError> {
+        let count = self.read_length()?;
+        let mut values = Vec::with_capacity(count);
+        let mut previous = 0u64;
+        for _ in 0..count {
+            previous = previous.wrapping_add(self.read_signed()? as u64);
+            values.push(previous);
+        }
+        Ok(values)
+    }
+
+    fn read_envelope(&mut self) -> Result<Map<String, Value>, InkError> {
+        let length = self.read_length()?;
+        if length == 0 {
+            return Ok(Map::new());
+        }
+        let bytes = self.read_bytes(length)?;
+        match serde_json::from_slice(bytes)? {
+            Value::Object(envelope) => Ok(envelope),
+            _ => Err(InkError::Schema(String::from("envelope is not a JSON object"))),
+        }
+    }
+
+    fn read_stroke(&mut self) -> Result<Stroke, InkError> {
+        let x = self.read_floats()?;
+        let y = self.read_floats()?;
+        let timestamp = self.read_timestamps()?;
+        let pressure = self.read_floats()?;
+        let mut stroke = Stroke::new(x, y, timestamp, pressure);
+
+        for (key, value) in self.read_envelope()? {
+            match key.as_str() {
+                "type" => stroke.typ = serde_json::from_value(value)?,
+                "meta" => stroke.meta = serde_json::from_value(value)?,
+                "channels" => stroke.channels = serde_json::from_value(value)?,
+                "style" => stroke.style = serde_json::from_value(value)?,
+                _ => return Err(InkError::Schema(format!("unknown stroke field `{}`", key))),
+            }
+        }
+        Ok(stroke)
+    }
+
+    fn read_sketch(&mut self) -> Result<Sketch, InkError> {
+        let count = self.read_length()?;
+        let strokes = (0..count).map(|_| self.read_stroke()).collect::<Result<_, _>>()?;
+        let mut sketch = Sketch::new(strokes);
+
+        for (key, value) in self.read_envelope()? {
+            match key.as_str() {
+                "type" => sketch.typ = serde_json::from_value(value)?,
+                "meta" => sketch.meta = serde_json::from_value(value)?,
+                "groups" => sketch.groups = serde_json::from_value(value)?,
+                "style" => sketch.style = serde_json::from_value(value)?,
+                "coordinate_system" => sketch.coordinate_system = serde_json::from_value(value)?,
+                _ => return Err(InkError::Schema(format!("unknown sketch field `{}`", key))),
+            }
+        }
+        Ok(sketch)
+    }
+
+    fn read_document(&mut self) -> Result<Document, InkError> {
+        let count = self.read_length()?;
+        let mut pages = Vec::with_capacity(count);
+        for _ in 0..count {
+            let width = self.read_f64()?;
+            let height = self.read_f64()?;
+            let mut page = Page::new(self.read_sketch()?, width, height, Unit::default());
+            for (key, value) in self.read_envelope()? {
+                match key.as_str() {
+                    "type" => page.typ = serde_json::from_value(value)?,
+                    "meta" => page.meta = serde_json::from_value(value)?,
+                    "unit" => page.unit = serde_json::from_value(value)?,
+                    _ => return Err(InkError::Schema(format!("unknown page field `{}`", key))),
+                }
+            }
+            pages.push(page);
+        }
+        let mut document = Document::new(pages);
+
+        for (key, value) in self.read_envelope()? {
+            match key.as_str() {
+                "type" => document.typ = serde_json::from_value(value)?,
+                "meta" => document.meta = serde_json::from_value(value)?,
+                _ => return Err(InkError::Schema(format!("unknown document field `{}`", key))),
+            }
+        }
+        Ok(document)
+    }
+}
+
+fn kind_name(kind: u8) -> String {
+    match kind {
+        KIND_STROKE => String::from("stroke"),
+        KIND_SKETCH => String::from("sketch"),
+        KIND_SKETCHES => String::from("sketch collection"),
+        KIND_STROKES => String::from("stroke collection"),
+        KIND_DOCUMENT => String::from("document"),
+        _ => format!("unknown object kind {}", kind),
+    }
+}
@@ -0,0 +1,162 @@
+//! Transparent compression of stored ink files
+//!
+//! Files are decompressed based on their magic bytes, so a compressed file is read correctly whatever its name.
+//! Written files are compressed based on their extension, `.gz` for gzip and `.zst` for zstd.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
+
+use crate::error::InkError;
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    /// Compression implied by the file extension, e.g. `sketch.json.gz`
+    pub fn from_path(file_path: impl AsRef<Path>) -> Compression {
+        let extension = file_path.as_ref().extension().and_then(|e| e.to_str()).map(|e| e.to_ascii_lowercase());
+        match extension.as_deref() {
+            Some("gz") | Some("gzip") | Some("svgz") => Compression::Gzip,
+            Some("zst") | Some("zstd") => Compression::Zstd,
+            _ => Compression::None,
+        }
+    }
+
+    /// Compression identified by the first bytes of a file
+    pub fn from_magic(bytes: &[u8]) -> Compression {
+        if bytes.starts_with(GZIP_MAGIC) {
+            Compression::Gzip
+        } else if bytes.starts_with(ZSTD_MAGIC) {
+            Compression::Zstd
+        } else {
+            Compression::None
+        }
+    }
+}
+
+/// Opens a file for reading, decompressing it if it starts with gzip or zstd magic bytes
+pub fn open(file_path: impl AsRef<Path>) -> Result<Box<dyn BufRead>, InkError> {
+    let mut reader = BufReader::new(File::open(file_path)?);
+    let compression = Compression::from_magic(reader.fill_buf()?);
+    Ok(match compression {
+        Compression::None => Box::new(reader),
+        Compression::Gzip => Box::new(BufReader::new(MultiGzDecoder::new(reader))),
+        Compression::Zstd => Box::new(BufReader::new(zstd::Decoder::with_buffer(reader)?)),
+    })
+}
+
+/// Reads a whole file like `fs::read`, decompressing it if needed
+pub fn read(file_path: impl AsRef<Path>) -> Result<Vec<u8>, InkError> {
+    let mut bytes = vec![];
+    open(file_path)?.read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Reads a whole file like `fs::read_to_string`, decompressing it if needed
+pub fn read_to_string(file_path: impl AsRef<Path>) -> Result<String, InkError> {
+    let mut contents = String::new();
+    open(file_path)?.read_to_string(&mut contents)?;
+    Ok(contents)
+}
+
+/// Creates or truncates a file for writing, compressed according to its extension
+pub fn create(file_path: impl AsRef<Path>) -> Result<CompressedWriter, InkError> {
+    let compression = Compression::from_path(&file_path);
+    CompressedWriter::new(File::create(file_path)?, compression)
+}
+
+/// Opens a file for appending, creating it if missing
+///
+/// Compressed data is appended as a new gzip member or zstd frame, which the readers of both formats
+/// concatenate when decompressing.
+pub fn append(file_path: impl AsRef<Path>) -> Result<CompressedWriter, InkError> {
+    let compression = Compression::from_path(&file_path);
+    CompressedWriter::new(OpenOptions::new().create(true).append(true).open(file_path)?, compression)
+}
+
+/// Writes a whole file like `fs::write`, compressed according to its extension
+pub fn write(file_path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> Result<(), InkError> {
+    let mut writer = create(file_path)?;
+    writer.write_all(contents.as_ref())?;
+    writer.finish()
+}
+
+enum Encoder {
+    None(BufWriter<File>),
+    Gzip(GzEncoder<BufWriter<File>>),
+    Zstd(zstd::Encoder<'static, BufWriter<File>>),
+}
+
+/// File writer compressing its output
+///
+/// The compressed stream is completed when the writer is dropped, call `finish` to be notified of errors.
+pub struct CompressedWriter {
+    encoder: Encoder,
+}
+
+impl CompressedWriter {
+    fn new(file: File, compression: Compression) -> Result<CompressedWriter, InkError> {
+        let writer = BufWriter::new(file);
+        let encoder = match compression {
+            Compression::None => Encoder::None(writer),
+            Compression::Gzip => Encoder::Gzip(GzEncoder::new(writer, flate2::Compression::default())),
+            Compression::Zstd => Encoder::Zstd(zstd::Encoder::new(writer, 0)?),
+        };
+        Ok(CompressedWriter { encoder })
+    }
+
+    /// Completes the compressed stream and flushes it to the file
+    pub fn finish(mut self) -> Result<(), InkError> {
+        self.try_finish()?;
+        Ok(())
+    }
+
+    fn try_finish(&mut self) -> io::Result<()> {
+        match &mut self.encoder {
+            Encoder::None(writer) => writer.flush(),
+            Encoder::Gzip(encoder) => {
+                encoder.try_finish()?;
+                encoder.get_mut().flush()
+            }
+            Encoder::Zstd(encoder) => {
+                encoder.do_finish()?;
+                encoder.get_mut().flush()
+            }
+        }
+    }
+}
+
+impl Write for CompressedWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match &mut self.encoder {
+            Encoder::None(writer) => writer.write(buf),
+            Encoder::Gzip(encoder) => encoder.write(buf),
+            Encoder::Zstd(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.encoder {
+            Encoder::None(writer) => writer.flush(),
+            Encoder::Gzip(encoder) => encoder.flush(),
+            Encoder::Zstd(encoder) => encoder.flush(),
+        }
+    }
+}
+
+impl Drop for CompressedWriter {
+    fn drop(&mut self) {
+        let _ = self.try_finish();
+    }
+}
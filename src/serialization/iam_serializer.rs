@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use roxmltree::{Document, Node};
+use serde_json::{json, Map, Value};
+
+use crate::error::InkError;
+use crate::serialization::compression;
+use crate::sketch::Sketch;
+use crate::stroke::Stroke;
+
+/// Reads an IAM-OnDB XML file, which are declared as ISO-8859-1 and decoded as such unless they are valid UTF-8
+pub fn load_sketch(file_path: impl AsRef<Path>) -> Result<Sketch, InkError> {
+    let bytes = compression::read(file_path)?;
+    let contents = match String::from_utf8(bytes) {
+        Ok(contents) => contents,
+        Err(e) => e.into_bytes().iter().map(|&b| b as char).collect(),
+    };
+    loads_sketch(contents)
+}
+
+/// Reads a `<WhiteboardCaptureSession>` of the IAM On-Line Handwriting Database into a sketch
+///
+/// Every `<Stroke>` of the `<StrokeSet>` becomes a stroke with its `colour` as style color. Point times are
+/// given in seconds and converted into milliseconds. The attributes of the elements within `<General>` end
+/// up in `meta` as `<element>_<attribute>` in snake case, e.g. `form_id`, except `writerID` which becomes
+/// `writer_id`. The corners of the `<WhiteboardDescription>` are kept as `whiteboard`, and the text lines of
+/// a `<Transcription>` as `transcription`, joined by newlines.
+pub fn loads_sketch(serialized_string: String) -> Result<Sketch, InkError> {
+    let document = Document::parse(serialized_string.as_str())?;
+    let session = document.root_element();
+    if session.tag_name().name() != "WhiteboardCaptureSession" {
+        return Err(InkError::Schema(format!("expected root element `WhiteboardCaptureSession`, found `{}`", session.tag_name().name())));
+    }
+
+    let mut strokes = vec![];
+    let mut meta = HashMap::new();
+    for child in session.children().filter(|n| n.is_element()) {
+        match child.tag_name().name() {
+            "StrokeSet" => {
+                for stroke in child.children().filter(|n| n.has_tag_name("Stroke")) {
+                    strokes.push(read_stroke(&stroke)?);
+                }
+            }
+            "General" => {
+                for element in child.children().filter(|n| n.is_element()) {
+                    for attribute in element.attributes() {
+                        let key = match attribute.name() {
+                            "writerID" => String::from("writer_id"),
+                            name => format!("{}_{}", snake_case(element.tag_name().name()), snake_case(name)),
+                        };
+                        meta.insert(key, json!(attribute.value()));
+                    }
+                }
+            }
+            "WhiteboardDescription" => {
+                let whiteboard: Map<String, Value> = child.children().filter(|n| n.is_element())
+                    .map(|element| (snake_case(element.tag_name().name()), attributes(&element)))
+                    .collect();
+                meta.insert(String::from("whiteboard"), Value::Object(whiteboard));
+            }
+            "Transcription" => {
+                meta.insert(String::from("transcription"), json!(read_transcription(&child)));
+            }
+            _ => {}
+        }
+    }
+
+    let mut sketch = Sketch::new(strokes);
+    sketch.meta = meta;
+    Ok(sketch)
+}
+
+fn read_stroke(node: &Node) -> Result<Stroke, InkError> {
+    let mut x = vec![];
+    let mut y = vec![];
+    let mut timestamp = vec![];
+    for point in node.children().filter(|n| n.has_tag_name("Point")) {
+        x.push(number_attribute(&point, "x")?);
+        y.push(number_attribute(&point, "y")?);
+        if point.has_attribute("time") {
+            timestamp.push((number_attribute(&point, "time")? * 1000.).max(0.).round() as u64);
+        }
+    }
+    if timestamp.len() != x.len() {
+        timestamp.clear();
+    }
+
+    let mut stroke = Stroke::new(x, y, timestamp, vec![]);
+    stroke.style.color = node.attribute("colour").map(String::from);
+    Ok(stroke)
+}
+
+fn number_attribute(node: &Node, name: &str) -> Result<f64, InkError> {
+    let value = node.attribute(name)
+        .ok_or_else(|| InkError::Schema(format!("`{}` element without `{}` attribute", node.tag_name().name(), name)))?;
+    value.trim().parse()
+        .map_err(|_| InkError::Schema(format!("invalid `{}` value `{}`", name, value)))
+}
+
+/// Attributes of an element as JSON object, numbers are converted where possible
+fn attributes(node: &Node) -> Value {
+    let attributes: Map<String, Value> = node.attributes()
+        .map(|a| {
+            let value = match a.value().parse::<f64>() {
+                Ok(number) => json!(number),
+                Err(_) => json!(a.value()),
+            };
+            (snake_case(a.name()), value)
+        })
+        .collect();
+    Value::Object(attributes)
+}
+
+/// Text lines of a transcription, falling back to its `<Text>` content
+fn read_transcription(node: &Node) -> String {
+    let lines: Vec<&str> = node.children()
+        .filter(|n| n.has_tag_name("TextLine"))
+        .filter_map(|n| n.attribute("text"))
+        .collect();
+    if !lines.is_empty() {
+        return lines.join("\n");
+    }
+
+    node.children()
+        .find(|n| n.has_tag_name("Text"))
+        .and_then(|n| n.text())
+        .map(|text| text.trim().lines().map(str::trim).collect::<Vec<&str>>().join("\n"))
+        .unwrap_or_default()
+}
+
+/// `writerID` → `writer_id`, `CaptureTime` → `capture_time`
+fn snake_case(name: &str) -> String {
+    let mut snake = String::new();
+    let chars: Vec<char> = name.chars().collect();
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() {
+            let previous_lower = i > 0 && (chars[i - 1].is_lowercase() || chars[i - 1].is_ascii_digit());
+            let next_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            if i > 0 && (previous_lower || next_lower && chars[i - 1].is_uppercase()) {
+                snake.push('_');
+            }
+            snake.extend(c.to_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+    snake
+}
@@ -0,0 +1,617 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use roxmltree::{Document, Node, NodeId};
+use serde_json::{json, Value};
+
+use crate::annotation::TraceGroup;
+use crate::channel::Channel;
+use crate::error::InkError;
+use crate::serialization::{compression, escape_xml as escape};
+use crate::sketch::Sketch;
+use crate::stroke::Stroke;
+use crate::style::StrokeStyle;
+
+const INKML_NAMESPACE: &str = "http://www.w3.org/2003/InkML";
+const XML_NAMESPACE: &str = "http://www.w3.org/XML/1998/namespace";
+
+/// Channels mapped onto `x`, `y`, `timestamp` and `pressure`
+const CHANNELS: &[&str] = &["X", "Y", "T", "F"];
+
+/// Annotation type of a `<traceGroup>` that is mapped onto the label of a `TraceGroup`
+pub const LABEL_ANNOTATION: &str = "truth";
+
+pub fn dump_stroke(stroke: &Stroke, file_path: impl AsRef<Path>) -> Result<(), InkError> {
+    let inkml_string = dumps_stroke(stroke);
+    compression::write(file_path, inkml_string)
+}
+
+pub fn dump_sketch(sketch: &Sketch, file_path: impl AsRef<Path>) -> Result<(), InkError> {
+    let inkml_string = dumps_sketch(sketch);
+    compression::write(file_path, inkml_string)
+}
+
+/// Writes a single stroke as an `<ink>` document holding one `<trace>`
+pub fn dumps_stroke(stroke: &Stroke) -> String {
+    let sketch = Sketch::new(vec![stroke.clone()]);
+    dumps_sketch(&sketch)
+}
+
+/// Writes a sketch as an `<ink>` document with one `<trace>` per stroke
+///
+/// The channels of the first non-empty stroke make up the document's `<traceFormat>`, strokes with other channels
+/// refer to a `<context>` declaring their own. Extra channels are written after X, Y, T and F with their type,
+/// booleans as `T` and `F`. Stroke styles become `<brush>` definitions referenced by the traces, the sketch
+/// style is the brush of the document's default context.
+pub fn dumps_sketch(sketch: &Sketch) -> String {
+    let default = sketch.strokes.iter().find(|s| !s.is_empty()).map_or_else(Layout::default, Layout::of);
+    // strokes without points fit any layout
+    let layout = |s: &Stroke| if s.is_empty() { default.clone() } else { Layout::of(s) };
+    let mut contexts: Vec<Layout> = sketch.strokes.iter().map(layout).filter(|l| *l != default).collect();
+    contexts.sort();
+    contexts.dedup();
+    let mut brushes: Vec<&StrokeStyle> = vec![];
+    for style in sketch.strokes.iter().map(|s| &s.style).filter(|s| !s.is_empty()) {
+        if !brushes.contains(&style) {
+            brushes.push(style);
+        }
+    }
+
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str(&format!("<ink xmlns=\"{}\">\n", INKML_NAMESPACE));
+
+    if !contexts.is_empty() || !brushes.is_empty() {
+        out.push_str("  <definitions>\n");
+        for (i, context) in contexts.iter().enumerate() {
+            out.push_str(&format!("    <context xml:id=\"ctx{}\">\n", i));
+            write_trace_format(&mut out, context, 3);
+            out.push_str("    </context>\n");
+        }
+        for (i, style) in brushes.iter().enumerate() {
+            write_brush(&mut out, Some(&format!("brush{}", i)), style, 2);
+        }
+        out.push_str("  </definitions>\n");
+    }
+    if sketch.style.is_empty() {
+        write_trace_format(&mut out, &default, 1);
+    } else {
+        out.push_str("  <context>\n");
+        write_trace_format(&mut out, &default, 2);
+        write_brush(&mut out, None, &sketch.style, 2);
+        out.push_str("  </context>\n");
+    }
+
+    let mut keys: Vec<&String> = sketch.meta.keys().collect();
+    keys.sort();
+    for key in keys {
+        write_annotation(&mut out, key, &sketch.meta[key], 1);
+    }
+
+    for (i, stroke) in sketch.strokes.iter().enumerate() {
+        let layout = layout(stroke);
+        let mut points = Vec::with_capacity(stroke.len());
+        for j in 0..stroke.len() {
+            let mut point = format!("{} {}", stroke.x[j], stroke.y[j]);
+            if layout.timestamp {
+                point.push_str(&format!(" {}", stroke.timestamp[j]));
+            }
+            if layout.pressure {
+                point.push_str(&format!(" {}", stroke.pressure[j]));
+            }
+            for (name, _) in layout.extra.iter() {
+                match &stroke.channels[name] {
+                    Channel::Float(values) => point.push_str(&format!(" {}", values[j])),
+                    Channel::Integer(values) => point.push_str(&format!(" {}", values[j])),
+                    Channel::Boolean(values) => point.push_str(if values[j] { " T" } else { " F" }),
+                }
+            }
+            points.push(point);
+        }
+        let mut references = String::new();
+        if let Some(context) = contexts.iter().position(|c| *c == layout) {
+            references.push_str(&format!(" contextRef=\"#ctx{}\"", context));
+        }
+        if let Some(brush) = brushes.iter().position(|&b| *b == stroke.style) {
+            references.push_str(&format!(" brushRef=\"#brush{}\"", brush));
+        }
+        out.push_str(&format!("  <trace xml:id=\"{}\"{}>{}</trace>\n", escape(&trace_id(stroke, i)), references, points.join(", ")));
+    }
+
+    for group in sketch.groups.iter() {
+        write_trace_group(&mut out, sketch, group, 1);
+    }
+
+    out.push_str("</ink>\n");
+    out
+}
+
+pub fn load_stroke(file_path: impl AsRef<Path>) -> Result<Stroke, InkError> {
+    let contents = compression::read_to_string(file_path)?;
+    loads_stroke(contents)
+}
+
+pub fn load_sketch(file_path: impl AsRef<Path>) -> Result<Sketch, InkError> {
+    let contents = compression::read_to_string(file_path)?;
+    loads_sketch(contents)
+}
+
+/// Reads the first `<trace>` of an InkML document
+pub fn loads_stroke(serialized_string: String) -> Result<Stroke, InkError> {
+    let mut sketch = loads_sketch(serialized_string)?;
+    if sketch.strokes.is_empty() {
+        return Err(InkError::Schema(String::from("InkML document does not contain a trace")));
+    }
+    Ok(sketch.strokes.swap_remove(0))
+}
+
+/// Reads all traces of an InkML document into a sketch
+///
+/// The channels X, Y, T and F are mapped onto `x`, `y`, `timestamp` and `pressure`, other channels become
+/// extra channels of their declared type and are skipped if a value does not match it. Brushes referenced by
+/// the traces become stroke styles and the brush of the default context the sketch style. Annotations of the
+/// `<ink>` element end up in the sketch's `meta`, trace groups become the sketch's `groups` named after their
+/// `xml:id`, with the `truth` annotation as label and all other annotations as attributes.
+pub fn loads_sketch(serialized_string: String) -> Result<Sketch, InkError> {
+    let document = Document::parse(serialized_string.as_str())?;
+    let ink = document.root_element();
+    if ink.tag_name().name() != "ink" {
+        return Err(InkError::Schema(format!("expected root element `ink`, found `{}`", ink.tag_name().name())));
+    }
+
+    let formats = TraceFormats::collect(&ink);
+    let brushes = ink.descendants()
+        .filter(|n| n.tag_name().name() == "brush")
+        .filter_map(|n| xml_id(&n).map(|id| (id, read_brush(&n))))
+        .collect();
+    let mut reader = InkReader { formats, brushes, strokes: vec![], trace_ids: HashMap::new(), trace_nodes: HashMap::new() };
+
+    let traces = ink.descendants()
+        .filter(|n| n.tag_name().name() == "trace")
+        .filter(|n| !n.ancestors().any(|a| a.tag_name().name() == "definitions"));
+    for trace in traces {
+        reader.read_trace(&trace)?;
+    }
+
+    let mut meta = HashMap::new();
+    let mut groups = vec![];
+    for child in ink.children().filter(|n| n.is_element()) {
+        match child.tag_name().name() {
+            "annotation" => {
+                let (key, value) = read_annotation(&child);
+                meta.insert(key, value);
+            }
+            "traceGroup" => groups.push(reader.read_trace_group(&child)),
+            _ => {}
+        }
+    }
+
+    let default_brush = ink.children()
+        .filter(|n| n.tag_name().name() == "context" && xml_id(n).is_none())
+        .find_map(|n| n.children().find(|b| b.tag_name().name() == "brush").map(|b| read_brush(&b))
+            .or_else(|| n.attribute("brushRef").and_then(|r| reader.brushes.get(r.trim_start_matches('#')).cloned())));
+
+    let mut sketch = Sketch::new(reader.strokes);
+    sketch.meta = meta;
+    sketch.groups = groups;
+    sketch.style = default_brush.unwrap_or_default();
+    Ok(sketch)
+}
+
+/// Channels written for a stroke besides X and Y, extra channels are sorted by name together with their InkML type
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+struct Layout {
+    timestamp: bool,
+    pressure: bool,
+    extra: Vec<(String, &'static str)>,
+}
+
+impl Layout {
+    /// Channels holding a value for every point, extra channels named like X, Y, T or F are left out
+    fn of(stroke: &Stroke) -> Layout {
+        let mut extra: Vec<(String, &'static str)> = stroke.channels.iter()
+            .filter(|(name, channel)| channel.len() == stroke.len() && !CHANNELS.contains(&name.as_str()))
+            .map(|(name, channel)| (name.clone(), match channel {
+                Channel::Float(_) => "decimal",
+                Channel::Integer(_) => "integer",
+                Channel::Boolean(_) => "boolean",
+            }))
+            .collect();
+        extra.sort();
+        Layout { timestamp: stroke.timestamp.len() == stroke.len(), pressure: stroke.pressure.len() == stroke.len(), extra }
+    }
+}
+
+/// `<traceFormat>` with X and Y, optionally T and F and the extra channels
+fn write_trace_format(out: &mut String, layout: &Layout, depth: usize) {
+    let indent = "  ".repeat(depth);
+    out.push_str(&format!("{}<traceFormat>\n", indent));
+    out.push_str(&format!("{}  <channel name=\"X\" type=\"decimal\"/>\n", indent));
+    out.push_str(&format!("{}  <channel name=\"Y\" type=\"decimal\"/>\n", indent));
+    if layout.timestamp {
+        out.push_str(&format!("{}  <channel name=\"T\" type=\"integer\"/>\n", indent));
+    }
+    if layout.pressure {
+        out.push_str(&format!("{}  <channel name=\"F\" type=\"decimal\"/>\n", indent));
+    }
+    for (name, typ) in layout.extra.iter() {
+        out.push_str(&format!("{}  <channel name=\"{}\" type=\"{}\"/>\n", indent, escape(name), typ));
+    }
+    out.push_str(&format!("{}</traceFormat>\n", indent));
+}
+
+/// `<brush>` with the properties `color`, `width`, `transparency` and `tool` of a style
+fn write_brush(out: &mut String, id: Option<&str>, style: &StrokeStyle, depth: usize) {
+    let indent = "  ".repeat(depth);
+    match id {
+        Some(id) => out.push_str(&format!("{}<brush xml:id=\"{}\">\n", indent, id)),
+        None => out.push_str(&format!("{}<brush>\n", indent)),
+    }
+    let mut properties = vec![];
+    if let Some(color) = &style.color {
+        properties.push(("color", color.clone()));
+    }
+    if let Some(width) = style.width {
+        properties.push(("width", width.to_string()));
+    }
+    if let Some(opacity) = style.opacity {
+        properties.push(("transparency", (1. - opacity).to_string()));
+    }
+    if let Some(tool) = style.tool {
+        properties.push(("tool", serde_json::to_value(tool).unwrap().as_str().unwrap_or_default().to_string()));
+    }
+    for (name, value) in properties {
+        out.push_str(&format!("{}  <brushProperty name=\"{}\" value=\"{}\"/>\n", indent, name, escape(&value)));
+    }
+    out.push_str(&format!("{}</brush>\n", indent));
+}
+
+fn trace_id(stroke: &Stroke, index: usize) -> String {
+    match stroke.meta.get("id") {
+        Some(Value::String(id)) => id.clone(),
+        _ => format!("t{}", index),
+    }
+}
+
+/// Strings are written as text, other values as JSON marked with `encoding="json"`
+fn write_annotation(out: &mut String, key: &str, value: &Value, depth: usize) {
+    let (encoding, text) = match value {
+        Value::String(s) => ("", s.clone()),
+        v => (" encoding=\"json\"", v.to_string()),
+    };
+    out.push_str(&format!("{}<annotation type=\"{}\"{}>{}</annotation>\n", "  ".repeat(depth), escape(key), encoding, escape(&text)));
+}
+
+fn write_trace_group(out: &mut String, sketch: &Sketch, group: &TraceGroup, depth: usize) {
+    let indent = "  ".repeat(depth);
+    if group.name.is_empty() {
+        out.push_str(&format!("{}<traceGroup>\n", indent));
+    } else {
+        out.push_str(&format!("{}<traceGroup xml:id=\"{}\">\n", indent, escape(&group.name)));
+    }
+
+    if let Some(label) = &group.label {
+        write_annotation(out, LABEL_ANNOTATION, &json!(label), depth + 1);
+    }
+    let mut keys: Vec<&String> = group.attributes.keys().collect();
+    keys.sort();
+    for key in keys {
+        write_annotation(out, key, &group.attributes[key], depth + 1);
+    }
+    for reference in group.strokes.iter() {
+        if let Some(stroke) = sketch.strokes.get(reference.stroke) {
+            out.push_str(&format!("{}  <traceView traceDataRef=\"#{}\"/>\n", indent, escape(&trace_id(stroke, reference.stroke))));
+        }
+    }
+    for child in group.groups.iter() {
+        write_trace_group(out, sketch, child, depth + 1);
+    }
+
+    out.push_str(&format!("{}</traceGroup>\n", indent));
+}
+
+fn xml_id(node: &Node) -> Option<String> {
+    node.attribute((XML_NAMESPACE, "id")).or_else(|| node.attribute("id")).map(String::from)
+}
+
+fn read_annotation(node: &Node) -> (String, Value) {
+    let key = node.attribute("type").unwrap_or("annotation");
+    let text = node.text().unwrap_or("").trim();
+    let value = match node.attribute("encoding") {
+        Some("json") => serde_json::from_str(text).unwrap_or_else(|_| json!(text)),
+        _ => json!(text),
+    };
+    (String::from(key), value)
+}
+
+/// Style from the properties of a `<brush>`, unknown properties are ignored
+fn read_brush(node: &Node) -> StrokeStyle {
+    let mut style = StrokeStyle::default();
+    for property in node.children().filter(|n| n.tag_name().name() == "brushProperty") {
+        let value = property.attribute("value").unwrap_or_default();
+        match property.attribute("name") {
+            Some("color") => style.color = Some(String::from(value)),
+            Some("width") => style.width = value.parse().ok(),
+            Some("transparency") => style.opacity = value.parse::<f64>().ok().map(|t| 1. - t),
+            Some("tool") => style.tool = serde_json::from_value(json!(value)).ok(),
+            _ => {}
+        }
+    }
+    style
+}
+
+/// Channel of a `<traceFormat>` with its declared type, `decimal` if none is given
+#[derive(Clone)]
+struct ChannelFormat {
+    name: String,
+    typ: String,
+}
+
+fn read_channels(trace_format: &Node) -> Vec<ChannelFormat> {
+    trace_format.children()
+        .filter(|n| n.is_element() && n.tag_name().name() == "channel")
+        .filter_map(|n| n.attribute("name").map(|name| ChannelFormat {
+            name: String::from(name),
+            typ: String::from(n.attribute("type").unwrap_or("decimal")),
+        }))
+        .collect()
+}
+
+/// Channel layouts declared in the document, looked up by `contextRef` of a trace
+struct TraceFormats {
+    default: Vec<ChannelFormat>,
+    contexts: HashMap<String, Vec<ChannelFormat>>,
+}
+
+impl TraceFormats {
+    fn collect(ink: &Node) -> TraceFormats {
+        let mut formats: HashMap<String, Vec<ChannelFormat>> = HashMap::new();
+        for node in ink.descendants().filter(|n| n.tag_name().name() == "traceFormat") {
+            if let Some(id) = xml_id(&node) {
+                formats.insert(id, read_channels(&node));
+            }
+        }
+
+        let mut contexts = HashMap::new();
+        let mut default = None;
+        for node in ink.descendants().filter(|n| n.tag_name().name() == "context") {
+            let channels = node.children()
+                .find(|n| n.tag_name().name() == "traceFormat")
+                .map(|n| read_channels(&n))
+                .or_else(|| node.attribute("traceFormatRef")
+                    .and_then(|r| formats.get(r.trim_start_matches('#')).cloned()));
+            if let Some(channels) = channels {
+                match xml_id(&node) {
+                    Some(id) => { contexts.insert(id, channels); }
+                    None => if default.is_none() { default = Some(channels) },
+                }
+            }
+        }
+
+        let default = default
+            .or_else(|| ink.children()
+                .find(|n| n.tag_name().name() == "traceFormat")
+                .map(|n| read_channels(&n)))
+            .or_else(|| ink.descendants()
+                .find(|n| n.tag_name().name() == "traceFormat")
+                .map(|n| read_channels(&n)))
+            .unwrap_or_else(|| ["X", "Y"].iter().map(|&name| ChannelFormat { name: String::from(name), typ: String::from("decimal") }).collect());
+
+        TraceFormats { default, contexts }
+    }
+
+    fn channels(&self, trace: &Node) -> &Vec<ChannelFormat> {
+        trace.attribute("contextRef")
+            .and_then(|r| self.contexts.get(r.trim_start_matches('#')))
+            .unwrap_or(&self.default)
+    }
+}
+
+struct InkReader {
+    formats: TraceFormats,
+    brushes: HashMap<String, StrokeStyle>,
+    strokes: Vec<Stroke>,
+    trace_ids: HashMap<String, usize>,
+    trace_nodes: HashMap<NodeId, usize>,
+}
+
+impl InkReader {
+    fn read_trace(&mut self, node: &Node) -> Result<(), InkError> {
+        let channels = self.formats.channels(node);
+        let columns: Vec<Column> = channels.iter()
+            .map(|c| match c.typ.as_str() {
+                _ if CHANNELS.contains(&c.name.as_str()) => Column::Mapped,
+                "boolean" => Column::Boolean,
+                _ => Column::Number,
+            })
+            .collect();
+        let values = parse_trace(node.text().unwrap_or(""), &columns)?;
+
+        let mut x = vec![];
+        let mut y = vec![];
+        let mut timestamp = vec![];
+        let mut pressure = vec![];
+        let mut extra = HashMap::new();
+        for (channel, column) in channels.iter().zip(values) {
+            let column = match column {
+                Some(column) => column,
+                None => continue,
+            };
+            match channel.name.as_str() {
+                "X" => x = column,
+                "Y" => y = column,
+                "T" => timestamp = column.iter().map(|&t| t.max(0.).round() as u64).collect(),
+                "F" => pressure = column,
+                name => {
+                    let channel = match channel.typ.as_str() {
+                        "boolean" => Channel::Boolean(column.iter().map(|&v| v != 0.).collect()),
+                        "integer" if column.iter().all(|&v| v >= 0. && v.fract() == 0.) => Channel::Integer(column.iter().map(|&v| v as u64).collect()),
+                        _ => Channel::Float(column),
+                    };
+                    extra.insert(String::from(name), channel);
+                }
+            }
+        }
+
+        let mut stroke = Stroke::new(x, y, timestamp, pressure);
+        stroke.channels = extra;
+        if let Some(style) = node.attribute("brushRef").and_then(|r| self.brushes.get(r.trim_start_matches('#'))) {
+            stroke.style = style.clone();
+        }
+        self.trace_nodes.insert(node.id(), self.strokes.len());
+        if let Some(id) = xml_id(node) {
+            self.trace_ids.insert(id.clone(), self.strokes.len());
+            stroke.meta.insert(String::from("id"), json!(id));
+        }
+        self.strokes.push(stroke);
+        Ok(())
+    }
+
+    /// Builds a group from its annotations, traces and `traceView` references, traces are read beforehand
+    fn read_trace_group(&self, node: &Node) -> TraceGroup {
+        let mut group = TraceGroup::new(&xml_id(node).unwrap_or_default());
+
+        for child in node.children().filter(|n| n.is_element()) {
+            match child.tag_name().name() {
+                "annotation" => {
+                    let (key, value) = read_annotation(&child);
+                    match (key.as_str(), value) {
+                        (LABEL_ANNOTATION, Value::String(label)) => group.label = Some(label),
+                        (_, value) => { group.attributes.insert(key, value); }
+                    }
+                }
+                "trace" => {
+                    if let Some(&index) = self.trace_nodes.get(&child.id()) {
+                        group.add_stroke(index);
+                    }
+                }
+                "traceView" => {
+                    let index = child.attribute("traceDataRef")
+                        .and_then(|r| self.trace_ids.get(r.trim_start_matches('#')));
+                    if let Some(&index) = index {
+                        group.add_stroke(index);
+                    }
+                }
+                "traceGroup" => group.add_group(self.read_trace_group(&child)),
+                _ => {}
+            }
+        }
+
+        group
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Encoding {
+    Explicit,
+    FirstDifference,
+    SecondDifference,
+}
+
+/// How the values of a channel are parsed
+#[derive(Clone, Copy, PartialEq)]
+enum Column {
+    /// Number that has to be valid, one of `CHANNELS`
+    Mapped,
+    /// Number of an extra channel
+    Number,
+    /// `T` or `F` of an extra channel, read as 1 and 0
+    Boolean,
+}
+
+/// Parses the content of a `<trace>` into one column per channel
+///
+/// Supports explicit values as well as first (`'`) and second (`"`) difference encoding of numbers. Invalid
+/// values of mapped channels are an error, while extra channels with an invalid value are skipped and left
+/// as `None`.
+fn parse_trace(text: &str, kinds: &[Column]) -> Result<Vec<Option<Vec<f64>>>, InkError> {
+    let channel_count = kinds.len();
+    let mut columns = vec![Some(vec![]); channel_count];
+    let mut encoding = vec![Encoding::Explicit; channel_count];
+    let mut previous = vec![0.; channel_count];
+    let mut velocity = vec![0.; channel_count];
+
+    for point in text.split(',') {
+        let tokens = tokenize_point(point);
+        if tokens.is_empty() {
+            continue;
+        }
+        if tokens.len() < channel_count {
+            return Err(InkError::Schema(format!("trace point `{}` has {} values, expected {}", point.trim(), tokens.len(), channel_count)));
+        }
+
+        for (i, (prefix, value)) in tokens.into_iter().take(channel_count).enumerate() {
+            let column = match columns[i].as_mut() {
+                Some(column) => column,
+                None => continue,
+            };
+            if kinds[i] == Column::Boolean {
+                match value.as_str() {
+                    "T" | "true" | "1" => column.push(1.),
+                    "F" | "false" | "0" => column.push(0.),
+                    _ => columns[i] = None,
+                }
+                continue;
+            }
+
+            match prefix {
+                Some('!') => encoding[i] = Encoding::Explicit,
+                Some('\'') => encoding[i] = Encoding::FirstDifference,
+                Some('"') => encoding[i] = Encoding::SecondDifference,
+                _ => {}
+            }
+            let value: f64 = match value.parse() {
+                Err(_) if kinds[i] == Column::Mapped => return Err(InkError::Schema(format!("invalid trace value `{}`", value))),
+                Err(_) => {
+                    columns[i] = None;
+                    continue;
+                }
+                Ok(v) => v,
+            };
+
+            let absolute = match encoding[i] {
+                Encoding::Explicit => {
+                    velocity[i] = if column.is_empty() { 0. } else { value - previous[i] };
+                    value
+                }
+                Encoding::FirstDifference => {
+                    velocity[i] = value;
+                    previous[i] + value
+                }
+                Encoding::SecondDifference => {
+                    velocity[i] += value;
+                    previous[i] + velocity[i]
+                }
+            };
+            previous[i] = absolute;
+            column.push(absolute);
+        }
+    }
+
+    Ok(columns)
+}
+
+fn tokenize_point(point: &str) -> Vec<(Option<char>, String)> {
+    let mut tokens = vec![];
+    let mut prefix = None;
+    let mut current = String::new();
+
+    for c in point.chars() {
+        let starts_token = c.is_whitespace() || c == '!' || c == '\'' || c == '"'
+            || (c == '-' && !current.is_empty() && !current.ends_with(['e', 'E']));
+        if starts_token && !current.is_empty() {
+            tokens.push((prefix.take(), current.clone()));
+            current.clear();
+        }
+        match c {
+            '!' | '\'' | '"' => prefix = Some(c),
+            c if c.is_whitespace() => {}
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push((prefix, current));
+    }
+
+    tokens
+}
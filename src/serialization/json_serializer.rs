@@ -1,71 +1,274 @@
-use std::fs;
+use std::io::{Read, Write};
+use std::path::Path;
 
+use serde::Serialize;
+
+use crate::document::Document;
+use crate::error::InkError;
+use crate::serialization::compression;
+use crate::serialization::{check_document, check_sketch, check_sketches, check_stroke, check_strokes, DumpOptions, LoadOptions};
 use crate::sketch::Sketch;
 use crate::stroke::Stroke;
 
-pub fn dump_stroke(stroke: &Stroke, file_path: &String) {
-    let json_string = dumps_stroke(stroke);
-    fs::write(file_path, json_string).expect("unable to write stroke to file");
+pub fn dump_stroke(stroke: &Stroke, file_path: impl AsRef<Path>) -> Result<(), InkError> {
+    dump_stroke_with_options(stroke, file_path, &DumpOptions::default())
+}
+
+pub fn dump_strokes(strokes: &[Stroke], file_path: impl AsRef<Path>) -> Result<(), InkError> {
+    dump_strokes_with_options(strokes, file_path, &DumpOptions::default())
+}
+
+pub fn dump_sketch(sketch: &Sketch, file_path: impl AsRef<Path>) -> Result<(), InkError> {
+    dump_sketch_with_options(sketch, file_path, &DumpOptions::default())
+}
+
+pub fn dump_sketches(sketches: &[Sketch], file_path: impl AsRef<Path>) -> Result<(), InkError> {
+    dump_sketches_with_options(sketches, file_path, &DumpOptions::default())
+}
+
+pub fn dump_document(document: &Document, file_path: impl AsRef<Path>) -> Result<(), InkError> {
+    dump_document_with_options(document, file_path, &DumpOptions::default())
+}
+
+pub fn dump_stroke_with_options(stroke: &Stroke, file_path: impl AsRef<Path>, options: &DumpOptions) -> Result<(), InkError> {
+    dump_value(stroke, file_path.as_ref(), options)
+}
+
+pub fn dump_strokes_with_options(strokes: &[Stroke], file_path: impl AsRef<Path>, options: &DumpOptions) -> Result<(), InkError> {
+    dump_value(strokes, file_path.as_ref(), options)
+}
+
+pub fn dump_sketch_with_options(sketch: &Sketch, file_path: impl AsRef<Path>, options: &DumpOptions) -> Result<(), InkError> {
+    dump_value(sketch, file_path.as_ref(), options)
 }
 
-pub fn dump_sketch(sketch: &Sketch, file_path: &String) {
-    let json_string = dumps_sketch(sketch);
-    fs::write(file_path, json_string).expect("unable to write sketch to file");
+pub fn dump_sketches_with_options(sketches: &[Sketch], file_path: impl AsRef<Path>, options: &DumpOptions) -> Result<(), InkError> {
+    dump_value(sketches, file_path.as_ref(), options)
+}
+
+pub fn dump_document_with_options(document: &Document, file_path: impl AsRef<Path>, options: &DumpOptions) -> Result<(), InkError> {
+    dump_value(document, file_path.as_ref(), options)
 }
 
 pub fn dumps_stroke(stroke: &Stroke) -> String {
-    serde_json::to_string_pretty(stroke).unwrap()
+    dumps_stroke_with_options(stroke, &DumpOptions::default())
+}
+
+pub fn dumps_strokes(strokes: &[Stroke]) -> String {
+    dumps_strokes_with_options(strokes, &DumpOptions::default())
 }
 
 pub fn dumps_sketch(sketch: &Sketch) -> String {
-    serde_json::to_string_pretty(sketch).unwrap()
+    dumps_sketch_with_options(sketch, &DumpOptions::default())
+}
+
+pub fn dumps_sketches(sketches: &[Sketch]) -> String {
+    dumps_sketches_with_options(sketches, &DumpOptions::default())
+}
+
+pub fn dumps_document(document: &Document) -> String {
+    dumps_document_with_options(document, &DumpOptions::default())
+}
+
+pub fn dumps_stroke_with_options(stroke: &Stroke, options: &DumpOptions) -> String {
+    dumps_value(stroke, options)
+}
+
+pub fn dumps_strokes_with_options(strokes: &[Stroke], options: &DumpOptions) -> String {
+    dumps_value(strokes, options)
+}
+
+pub fn dumps_sketch_with_options(sketch: &Sketch, options: &DumpOptions) -> String {
+    dumps_value(sketch, options)
+}
+
+pub fn dumps_sketches_with_options(sketches: &[Sketch], options: &DumpOptions) -> String {
+    dumps_value(sketches, options)
+}
+
+pub fn dumps_document_with_options(document: &Document, options: &DumpOptions) -> String {
+    dumps_value(document, options)
+}
+
+pub fn write_stroke(stroke: &Stroke, writer: impl Write, options: &DumpOptions) -> Result<(), InkError> {
+    write_value(stroke, writer, options)
+}
+
+pub fn write_strokes(strokes: &[Stroke], writer: impl Write, options: &DumpOptions) -> Result<(), InkError> {
+    write_value(strokes, writer, options)
+}
+
+pub fn write_sketch(sketch: &Sketch, writer: impl Write, options: &DumpOptions) -> Result<(), InkError> {
+    write_value(sketch, writer, options)
+}
+
+pub fn write_sketches(sketches: &[Sketch], writer: impl Write, options: &DumpOptions) -> Result<(), InkError> {
+    write_value(sketches, writer, options)
+}
+
+pub fn write_document(document: &Document, writer: impl Write, options: &DumpOptions) -> Result<(), InkError> {
+    write_value(document, writer, options)
+}
+
+pub fn load_stroke(file_path: impl AsRef<Path>) -> Result<Stroke, InkError> {
+    load_stroke_with_options(file_path, &LoadOptions::default())
+}
+
+pub fn load_strokes(file_path: impl AsRef<Path>) -> Result<Vec<Stroke>, InkError> {
+    load_strokes_with_options(file_path, &LoadOptions::default())
+}
+
+pub fn load_sketch(file_path: impl AsRef<Path>) -> Result<Sketch, InkError> {
+    load_sketch_with_options(file_path, &LoadOptions::default())
+}
+
+pub fn load_sketches(file_path: impl AsRef<Path>) -> Result<Vec<Sketch>, InkError> {
+    load_sketches_with_options(file_path, &LoadOptions::default())
+}
+
+pub fn load_document(file_path: impl AsRef<Path>) -> Result<Document, InkError> {
+    load_document_with_options(file_path, &LoadOptions::default())
+}
+
+pub fn load_stroke_with_options(file_path: impl AsRef<Path>, options: &LoadOptions) -> Result<Stroke, InkError> {
+    read_stroke_with_options(compression::open(file_path)?, options)
+}
+
+pub fn load_strokes_with_options(file_path: impl AsRef<Path>, options: &LoadOptions) -> Result<Vec<Stroke>, InkError> {
+    read_strokes_with_options(compression::open(file_path)?, options)
+}
+
+pub fn load_sketch_with_options(file_path: impl AsRef<Path>, options: &LoadOptions) -> Result<Sketch, InkError> {
+    read_sketch_with_options(compression::open(file_path)?, options)
+}
+
+pub fn load_sketches_with_options(file_path: impl AsRef<Path>, options: &LoadOptions) -> Result<Vec<Sketch>, InkError> {
+    read_sketches_with_options(compression::open(file_path)?, options)
+}
+
+pub fn load_document_with_options(file_path: impl AsRef<Path>, options: &LoadOptions) -> Result<Document, InkError> {
+    read_document_with_options(compression::open(file_path)?, options)
+}
+
+pub fn loads_stroke(serialized_string: String) -> Result<Stroke, InkError> {
+    loads_stroke_with_options(serialized_string, &LoadOptions::default())
+}
+
+pub fn loads_strokes(serialized_string: String) -> Result<Vec<Stroke>, InkError> {
+    loads_strokes_with_options(serialized_string, &LoadOptions::default())
+}
+
+pub fn loads_sketch(serialized_string: String) -> Result<Sketch, InkError> {
+    loads_sketch_with_options(serialized_string, &LoadOptions::default())
+}
+
+pub fn loads_sketches(serialized_string: String) -> Result<Vec<Sketch>, InkError> {
+    loads_sketches_with_options(serialized_string, &LoadOptions::default())
+}
+
+pub fn loads_document(serialized_string: String) -> Result<Document, InkError> {
+    loads_document_with_options(serialized_string, &LoadOptions::default())
+}
+
+pub fn loads_stroke_with_options(serialized_string: String, options: &LoadOptions) -> Result<Stroke, InkError> {
+    let stroke: Stroke = serde_json::from_str(serialized_string.as_str())?;
+    check_stroke(&stroke, 0, options)?;
+    Ok(stroke)
+}
+
+pub fn loads_strokes_with_options(serialized_string: String, options: &LoadOptions) -> Result<Vec<Stroke>, InkError> {
+    let strokes: Vec<Stroke> = serde_json::from_str(serialized_string.as_str())?;
+    check_strokes(&strokes, options)?;
+    Ok(strokes)
+}
+
+pub fn loads_sketch_with_options(serialized_string: String, options: &LoadOptions) -> Result<Sketch, InkError> {
+    let sketch: Sketch = serde_json::from_str(serialized_string.as_str())?;
+    check_sketch(&sketch, options)?;
+    Ok(sketch)
+}
+
+pub fn loads_sketches_with_options(serialized_string: String, options: &LoadOptions) -> Result<Vec<Sketch>, InkError> {
+    let sketches: Vec<Sketch> = serde_json::from_str(serialized_string.as_str())?;
+    check_sketches(&sketches, options)?;
+    Ok(sketches)
+}
+
+pub fn loads_document_with_options(serialized_string: String, options: &LoadOptions) -> Result<Document, InkError> {
+    let document: Document = serde_json::from_str(serialized_string.as_str())?;
+    check_document(&document, options)?;
+    Ok(document)
+}
+
+pub fn read_stroke(reader: impl Read) -> Result<Stroke, InkError> {
+    read_stroke_with_options(reader, &LoadOptions::default())
+}
+
+pub fn read_strokes(reader: impl Read) -> Result<Vec<Stroke>, InkError> {
+    read_strokes_with_options(reader, &LoadOptions::default())
+}
+
+pub fn read_sketch(reader: impl Read) -> Result<Sketch, InkError> {
+    read_sketch_with_options(reader, &LoadOptions::default())
+}
+
+pub fn read_sketches(reader: impl Read) -> Result<Vec<Sketch>, InkError> {
+    read_sketches_with_options(reader, &LoadOptions::default())
+}
+
+pub fn read_document(reader: impl Read) -> Result<Document, InkError> {
+    read_document_with_options(reader, &LoadOptions::default())
+}
+
+pub fn read_stroke_with_options(reader: impl Read, options: &LoadOptions) -> Result<Stroke, InkError> {
+    let stroke: Stroke = serde_json::from_reader(reader)?;
+    check_stroke(&stroke, 0, options)?;
+    Ok(stroke)
 }
 
-pub fn load_stroke(file_path: &String) -> Result<Stroke, String> {
-    let contents = fs::read_to_string(file_path).expect("unable to read stroke from file");
-    loads_stroke(contents)
+pub fn read_strokes_with_options(reader: impl Read, options: &LoadOptions) -> Result<Vec<Stroke>, InkError> {
+    let strokes: Vec<Stroke> = serde_json::from_reader(reader)?;
+    check_strokes(&strokes, options)?;
+    Ok(strokes)
 }
 
-pub fn load_sketch(file_path: &String) -> Result<Sketch, String> {
-    let contents = fs::read_to_string(file_path).expect("unable to read stroke from file");
-    match loads_sketch(contents) {
-        Err(e) => return Err(e.to_string()),
-        Ok(s) => return Ok(s),
-    };
+pub fn read_sketch_with_options(reader: impl Read, options: &LoadOptions) -> Result<Sketch, InkError> {
+    let sketch: Sketch = serde_json::from_reader(reader)?;
+    check_sketch(&sketch, options)?;
+    Ok(sketch)
 }
 
-pub fn load_sketches(file_path: &String) -> Result<Vec<Sketch>, String> {
-    let contents = fs::read_to_string(file_path).expect("unable to read stroke from file");
-    match loads_sketches(contents) {
-        Err(e) => return Err(e.to_string()),
-        Ok(s) => return Ok(s),
-    };
+pub fn read_sketches_with_options(reader: impl Read, options: &LoadOptions) -> Result<Vec<Sketch>, InkError> {
+    let sketches: Vec<Sketch> = serde_json::from_reader(reader)?;
+    check_sketches(&sketches, options)?;
+    Ok(sketches)
 }
 
-pub fn loads_stroke(serialized_string: String) -> Result<Stroke, String> {
-    match serde_json::from_str(serialized_string.as_str()) {
-        Err(e) => return Err(e.to_string()),
-        Ok(s) => return Ok(s),
-    };
+pub fn read_document_with_options(reader: impl Read, options: &LoadOptions) -> Result<Document, InkError> {
+    let document: Document = serde_json::from_reader(reader)?;
+    check_document(&document, options)?;
+    Ok(document)
 }
 
-pub fn loads_strokes(serialized_string: String) -> Result<Vec<Stroke>, String> {
-    match serde_json::from_str(serialized_string.as_str()) {
-        Err(e) => return Err(e.to_string()),
-        Ok(s) => return Ok(s),
-    };
+fn dump_value<T: Serialize + ?Sized>(value: &T, file_path: &Path, options: &DumpOptions) -> Result<(), InkError> {
+    let mut writer = compression::create(file_path)?;
+    write_value(value, &mut writer, options)?;
+    writer.finish()
 }
 
-pub fn loads_sketch(serialized_string: String) -> Result<Sketch, String> {
-    match serde_json::from_str(serialized_string.as_str()) {
-        Err(e) => return Err(e.to_string()),
-        Ok(s) => return Ok(s),
-    };
+fn dumps_value<T: Serialize + ?Sized>(value: &T, options: &DumpOptions) -> String {
+    if options.pretty {
+        serde_json::to_string_pretty(value).unwrap()
+    } else {
+        serde_json::to_string(value).unwrap()
+    }
 }
 
-pub fn loads_sketches(serialized_string: String) -> Result<Vec<Sketch>, String> {
-    match serde_json::from_str(serialized_string.as_str()) {
-        Err(e) => return Err(e.to_string()),
-        Ok(s) => return Ok(s),
-    };
+fn write_value<T: Serialize + ?Sized>(value: &T, writer: impl Write, options: &DumpOptions) -> Result<(), InkError> {
+    if options.pretty {
+        serde_json::to_writer_pretty(writer, value)?;
+    } else {
+        serde_json::to_writer(writer, value)?;
+    }
+    Ok(())
 }
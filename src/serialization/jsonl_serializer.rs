@@ -0,0 +1,194 @@
+//! JSON Lines collections of sketches, one compact JSON sketch per line
+//!
+//! The reader and the writer work incrementally, so collections larger than the available memory can be
+//! processed sketch by sketch.
+
+use std::io::{BufRead, Write};
+use std::path::Path;
+
+use serde_json::error::Category;
+
+use crate::error::InkError;
+use crate::serialization::compression::{self, CompressedWriter};
+use crate::serialization::{check_sketch, LoadOptions};
+use crate::sketch::Sketch;
+
+/// Non-blank lines of a newline-delimited stream, each handed to a parse function with its 1-based number
+///
+/// Reading stops after an I/O error, errors returned by the parse function do not stop reading.
+pub(crate) struct LineReader<R: BufRead> {
+    reader: R,
+    line_number: usize,
+    buffer: String,
+    finished: bool,
+}
+
+impl<R: BufRead> LineReader<R> {
+    pub(crate) fn new(reader: R) -> LineReader<R> {
+        LineReader { reader, line_number: 0, buffer: String::new(), finished: false }
+    }
+
+    pub(crate) fn line_number(&self) -> usize {
+        self.line_number
+    }
+
+    /// Reads up to the next non-blank line and parses it, `None` at the end of the stream
+    pub(crate) fn next_with<T>(&mut self, parse: impl FnOnce(&str, usize) -> Result<T, InkError>) -> Option<Result<T, InkError>> {
+        while !self.finished {
+            self.buffer.clear();
+            match self.reader.read_line(&mut self.buffer) {
+                Ok(0) => self.finished = true,
+                Ok(_) => {
+                    self.line_number += 1;
+                    if !self.buffer.trim().is_empty() {
+                        return Some(parse(&self.buffer, self.line_number));
+                    }
+                }
+                Err(e) => {
+                    self.finished = true;
+                    return Some(Err(InkError::Io(e)));
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Iterator over the sketches of a JSON Lines stream, blank lines are skipped
+///
+/// Malformed and, when loading strictly, invalid lines yield an error with the 1-based line number and reading
+/// continues with the next line. Reading stops after an I/O error.
+pub struct SketchReader<R: BufRead> {
+    lines: LineReader<R>,
+    options: LoadOptions,
+}
+
+impl<R: BufRead> SketchReader<R> {
+    pub fn new(reader: R) -> SketchReader<R> {
+        SketchReader::with_options(reader, LoadOptions::default())
+    }
+
+    pub fn with_options(reader: R, options: LoadOptions) -> SketchReader<R> {
+        SketchReader { lines: LineReader::new(reader), options }
+    }
+
+    /// Line number of the last line read, 1-based
+    pub fn line_number(&self) -> usize {
+        self.lines.line_number()
+    }
+}
+
+fn parse_sketch(line: &str, line_number: usize, options: &LoadOptions) -> Result<Sketch, InkError> {
+    let sketch = serde_json::from_str(line).map_err(|e| line_error(e, line_number))?;
+    match check_sketch(&sketch, options) {
+        Err(InkError::Validation(issues)) => Err(InkError::LineValidation { line: line_number, issues }),
+        result => result.map(|_| sketch),
+    }
+}
+
+impl<R: BufRead> Iterator for SketchReader<R> {
+    type Item = Result<Sketch, InkError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let options = &self.options;
+        self.lines.next_with(|line, line_number| parse_sketch(line, line_number, options))
+    }
+}
+
+/// Maps an error of a single line to its position in the stream
+pub(crate) fn line_error(e: serde_json::Error, line_number: usize) -> InkError {
+    match e.classify() {
+        Category::Io => InkError::Io(e.into()),
+        Category::Syntax | Category::Eof => InkError::Parse { message: message(&e), line: line_number, column: e.column() },
+        Category::Data => InkError::Schema(format!("line {}: {}", line_number, message(&e))),
+    }
+}
+
+/// Message of a JSON error without the position serde appends, which is relative to the line and not the stream
+fn message(e: &serde_json::Error) -> String {
+    let message = e.to_string();
+    let position = format!(" at line {} column {}", e.line(), e.column());
+    match message.strip_suffix(&position) {
+        Some(stripped) if e.line() > 0 => String::from(stripped),
+        _ => message,
+    }
+}
+
+/// Writes sketches one per line as they come, without holding the collection in memory
+pub struct SketchWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> SketchWriter<W> {
+    pub fn new(writer: W) -> SketchWriter<W> {
+        SketchWriter { writer }
+    }
+
+    pub fn write_sketch(&mut self, sketch: &Sketch) -> Result<(), InkError> {
+        serde_json::to_writer(&mut self.writer, sketch)?;
+        self.writer.write_all(b"\n")?;
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> Result<(), InkError> {
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    /// Flushes and returns the underlying writer
+    pub fn into_inner(mut self) -> Result<W, InkError> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Opens a JSON Lines file for reading sketch by sketch
+pub fn read_sketches(file_path: impl AsRef<Path>) -> Result<SketchReader<Box<dyn BufRead>>, InkError> {
+    read_sketches_with_options(file_path, &LoadOptions::default())
+}
+
+pub fn read_sketches_with_options(file_path: impl AsRef<Path>, options: &LoadOptions) -> Result<SketchReader<Box<dyn BufRead>>, InkError> {
+    Ok(SketchReader::with_options(compression::open(file_path)?, options.clone()))
+}
+
+/// Creates or truncates a JSON Lines file for writing, see `compression::create`
+pub fn create_sketches(file_path: impl AsRef<Path>) -> Result<SketchWriter<CompressedWriter>, InkError> {
+    Ok(SketchWriter::new(compression::create(file_path)?))
+}
+
+/// Opens a JSON Lines file for appending, creating it if missing, see `compression::append`
+pub fn append_sketches(file_path: impl AsRef<Path>) -> Result<SketchWriter<CompressedWriter>, InkError> {
+    Ok(SketchWriter::new(compression::append(file_path)?))
+}
+
+pub fn dump_sketches(sketches: &[Sketch], file_path: impl AsRef<Path>) -> Result<(), InkError> {
+    let mut writer = create_sketches(file_path)?;
+    for sketch in sketches.iter() {
+        writer.write_sketch(sketch)?;
+    }
+    writer.into_inner()?.finish()
+}
+
+pub fn dumps_sketches(sketches: &[Sketch]) -> String {
+    let mut writer = SketchWriter::new(Vec::new());
+    for sketch in sketches.iter() {
+        writer.write_sketch(sketch).unwrap();
+    }
+    String::from_utf8(writer.writer).unwrap()
+}
+
+pub fn load_sketches(file_path: impl AsRef<Path>) -> Result<Vec<Sketch>, InkError> {
+    load_sketches_with_options(file_path, &LoadOptions::default())
+}
+
+pub fn load_sketches_with_options(file_path: impl AsRef<Path>, options: &LoadOptions) -> Result<Vec<Sketch>, InkError> {
+    read_sketches_with_options(file_path, options)?.collect()
+}
+
+pub fn loads_sketches(serialized_string: String) -> Result<Vec<Sketch>, InkError> {
+    loads_sketches_with_options(serialized_string, &LoadOptions::default())
+}
+
+pub fn loads_sketches_with_options(serialized_string: String, options: &LoadOptions) -> Result<Vec<Sketch>, InkError> {
+    SketchReader::with_options(serialized_string.as_bytes(), options.clone()).collect()
+}
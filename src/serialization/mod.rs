@@ -1,5 +1,112 @@
+use crate::document::Document;
+use crate::error::InkError;
+use crate::sketch::Sketch;
+use crate::stroke::Stroke;
+
+pub mod compression;
 pub mod json_serializer;
+pub mod binary_serializer;
+pub mod jsonl_serializer;
+pub mod numpy_serializer;
+pub mod iam_serializer;
+pub mod inkml_serializer;
+pub mod quickdraw_serializer;
+pub mod svg_serializer;
+pub mod unipen_serializer;
 
 pub trait SerializableInkObject {
     fn to_json(&self) -> String;
-}
\ No newline at end of file
+}
+
+/// Options applied when deserializing ink objects
+#[derive(Clone, Debug, Default)]
+pub struct LoadOptions {
+    /// Validate loaded strokes and sketches and fail with `InkError::Validation` on invalid data
+    pub strict: bool,
+}
+
+impl LoadOptions {
+    pub fn strict() -> LoadOptions {
+        LoadOptions { strict: true }
+    }
+}
+
+/// Options applied when serializing ink objects
+#[derive(Clone, Debug)]
+pub struct DumpOptions {
+    /// Indent the output for readability, otherwise everything is written on a single line
+    pub pretty: bool,
+}
+
+impl DumpOptions {
+    pub fn compact() -> DumpOptions {
+        DumpOptions { pretty: false }
+    }
+}
+
+impl Default for DumpOptions {
+    fn default() -> Self {
+        DumpOptions { pretty: true }
+    }
+}
+
+/// Checks a stroke, issues are reported with `index` as the position of the stroke in its collection
+pub(crate) fn check_stroke(stroke: &Stroke, index: usize, options: &LoadOptions) -> Result<(), InkError> {
+    if options.strict {
+        if let Err(issues) = stroke.validate() {
+            return Err(InkError::Validation(issues.into_iter().map(|issue| (index, issue)).collect()));
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn check_strokes(strokes: &[Stroke], options: &LoadOptions) -> Result<(), InkError> {
+    for (index, stroke) in strokes.iter().enumerate() {
+        check_stroke(stroke, index, options)?;
+    }
+    Ok(())
+}
+
+pub(crate) fn check_sketch(sketch: &Sketch, options: &LoadOptions) -> Result<(), InkError> {
+    if options.strict {
+        if let Err(issues) = sketch.validate() {
+            return Err(InkError::Validation(issues));
+        }
+    }
+    Ok(())
+}
+
+/// Checks a sketch of a collection, failures are reported with `index` and the stroke indices within the sketch
+pub(crate) fn check_sketch_at(sketch: &Sketch, index: usize, options: &LoadOptions) -> Result<(), InkError> {
+    match check_sketch(sketch, options) {
+        Err(InkError::Validation(issues)) => Err(InkError::SketchValidation { sketch: index, issues }),
+        result => result,
+    }
+}
+
+pub(crate) fn check_sketches(sketches: &[Sketch], options: &LoadOptions) -> Result<(), InkError> {
+    for (index, sketch) in sketches.iter().enumerate() {
+        check_sketch_at(sketch, index, options)?;
+    }
+    Ok(())
+}
+
+/// Checks every page, the first invalid page is reported with its index and the stroke indices within it
+pub(crate) fn check_document(document: &Document, options: &LoadOptions) -> Result<(), InkError> {
+    for (page, sketch) in document.pages.iter().map(|p| &p.sketch).enumerate() {
+        match check_sketch(sketch, options) {
+            Err(InkError::Validation(issues)) => return Err(InkError::PageValidation { page, issues }),
+            result => result?,
+        }
+    }
+    Ok(())
+}
+
+/// Escapes text for use in XML content and attribute values
+pub(crate) fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
@@ -0,0 +1,385 @@
+//! NumPy `.npy` and `.npz` files of sketch tensors
+//!
+//! A sketch is stored as a float64 `points` array with one row per point and the columns `COLUMNS`, missing
+//! timestamps and pressure are NaN. Collections are stored in an `.npz` archive holding `points` together
+//! with an int64 `lengths` vector counting the points of every sketch. Depending on the layout `points` is
+//! either the concatenation of all sketches with shape `(points, 5)`, or a zero-padded tensor with shape
+//! `(sketches, longest sketch, 5)`. The archive also holds the int64 vectors `strokes`, counting the strokes
+//! of every sketch, and `stroke_lengths`, counting the points of every stroke, so that strokes without points
+//! survive the round trip. A single `.npy` array has no room for them and sketches with empty strokes are
+//! rejected. `meta` is not represented.
+
+use std::io::{Cursor, Read, Write};
+use std::path::Path;
+
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::error::InkError;
+use crate::serialization::compression;
+use crate::sketch::Sketch;
+use crate::stroke::Stroke;
+
+/// Columns of the `points` arrays
+pub const COLUMNS: [&str; 5] = ["x", "y", "timestamp", "pressure", "stroke"];
+
+const MAGIC: &[u8] = b"\x93NUMPY";
+
+/// Element values of an array, in C order
+#[derive(Clone, Debug, PartialEq)]
+pub enum NpyData {
+    Float(Vec<f64>),
+    Integer(Vec<i64>),
+}
+
+/// N-dimensional array as stored in a `.npy` file
+#[derive(Clone, Debug, PartialEq)]
+pub struct NpyArray {
+    pub shape: Vec<usize>,
+    pub data: NpyData,
+}
+
+impl NpyArray {
+    /// Values converted to float, e.g. for integer arrays written by other tools
+    pub fn to_floats(&self) -> Vec<f64> {
+        match &self.data {
+            NpyData::Float(values) => values.clone(),
+            NpyData::Integer(values) => values.iter().map(|&v| v as f64).collect(),
+        }
+    }
+}
+
+/// Arrangement of a sketch collection in an `.npz` archive
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NumpyLayout {
+    /// Points of all sketches concatenated into one matrix
+    Points,
+    /// Points of every sketch zero-padded to the length of the longest sketch
+    Padded,
+}
+
+pub fn dump_sketch(sketch: &Sketch, file_path: impl AsRef<Path>) -> Result<(), InkError> {
+    compression::write(file_path, dumps_sketch(sketch)?)
+}
+
+pub fn dump_sketches(sketches: &[Sketch], file_path: impl AsRef<Path>, layout: NumpyLayout) -> Result<(), InkError> {
+    compression::write(file_path, dumps_sketches(sketches, layout)?)
+}
+
+/// Serializes the `points` array of a sketch as `.npy`, fails for sketches with empty strokes
+pub fn dumps_sketch(sketch: &Sketch) -> Result<Vec<u8>, InkError> {
+    if let Some(i) = sketch.strokes.iter().position(|s| s.is_empty()) {
+        return Err(InkError::Schema(format!("stroke {} has no points, which a .npy array cannot represent", i)));
+    }
+    Ok(write_npy(&points(sketch)))
+}
+
+/// Serializes a collection as `.npz` holding `points`, `lengths`, `strokes` and `stroke_lengths`
+pub fn dumps_sketches(sketches: &[Sketch], layout: NumpyLayout) -> Result<Vec<u8>, InkError> {
+    let rows: Vec<NpyArray> = sketches.iter().map(points).collect();
+    let lengths: Vec<i64> = rows.iter().map(|r| r.shape[0] as i64).collect();
+    let values = |array: &NpyArray| array.to_floats();
+
+    let points = match layout {
+        NumpyLayout::Points => {
+            let total = lengths.iter().sum::<i64>() as usize;
+            NpyArray { shape: vec![total, COLUMNS.len()], data: NpyData::Float(rows.iter().flat_map(values).collect()) }
+        }
+        NumpyLayout::Padded => {
+            let longest = lengths.iter().copied().max().unwrap_or(0) as usize;
+            let mut data = Vec::with_capacity(sketches.len() * longest * COLUMNS.len());
+            for array in rows.iter() {
+                data.extend(values(array));
+                data.resize(data.len() + (longest - array.shape[0]) * COLUMNS.len(), 0.);
+            }
+            NpyArray { shape: vec![sketches.len(), longest, COLUMNS.len()], data: NpyData::Float(data) }
+        }
+    };
+    let lengths = NpyArray { shape: vec![sketches.len()], data: NpyData::Integer(lengths) };
+    let strokes: Vec<i64> = sketches.iter().map(|s| s.strokes.len() as i64).collect();
+    let strokes = NpyArray { shape: vec![sketches.len()], data: NpyData::Integer(strokes) };
+    let stroke_lengths: Vec<i64> = sketches.iter().flat_map(|s| s.strokes.iter().map(|s| s.len() as i64)).collect();
+    let stroke_lengths = NpyArray { shape: vec![stroke_lengths.len()], data: NpyData::Integer(stroke_lengths) };
+
+    write_npz(&[("points", &points), ("lengths", &lengths), ("strokes", &strokes), ("stroke_lengths", &stroke_lengths)])
+}
+
+pub fn load_sketch(file_path: impl AsRef<Path>) -> Result<Sketch, InkError> {
+    loads_sketch(compression::read(file_path)?)
+}
+
+pub fn load_sketches(file_path: impl AsRef<Path>) -> Result<Vec<Sketch>, InkError> {
+    loads_sketches(compression::read(file_path)?)
+}
+
+/// Reads a sketch from a `.npy` `points` array
+pub fn loads_sketch(serialized_bytes: Vec<u8>) -> Result<Sketch, InkError> {
+    let array = read_npy(&serialized_bytes)?;
+    if array.shape.len() != 2 || array.shape[1] < 2 {
+        return Err(InkError::Schema(format!("expected a points matrix, found shape {:?}", array.shape)));
+    }
+    Ok(from_rows(&array.to_floats(), array.shape[1], None))
+}
+
+/// Reads a collection from an `.npz` archive in either layout
+///
+/// Archives without `strokes` and `stroke_lengths`, e.g. written by other tools, are split into strokes by
+/// the stroke column.
+pub fn loads_sketches(serialized_bytes: Vec<u8>) -> Result<Vec<Sketch>, InkError> {
+    let mut arrays = read_npz(serialized_bytes)?;
+    let mut take = |name: &str| {
+        let position = arrays.iter().position(|(n, _)| n == name)?;
+        Some(arrays.swap_remove(position).1)
+    };
+    let missing = |name: &str| InkError::Schema(format!("archive does not contain `{}`", name));
+    let counts = |array: NpyArray| array.to_floats().iter().map(|&l| l.max(0.) as usize).collect::<Vec<usize>>();
+    let points = take("points").ok_or_else(|| missing("points"))?;
+    let lengths = counts(take("lengths").ok_or_else(|| missing("lengths"))?);
+    let strokes = match (take("strokes"), take("stroke_lengths")) {
+        (Some(strokes), Some(stroke_lengths)) => Some(split_strokes(&lengths, counts(strokes), counts(stroke_lengths))?),
+        _ => None,
+    };
+
+    let total = lengths.iter().try_fold(0usize, |total, &length| total.checked_add(length));
+    let values = points.to_floats();
+    let (columns, stride) = match points.shape.as_slice() {
+        &[rows, columns] if total == Some(rows) => (columns, None),
+        &[sketches, longest, columns] if sketches == lengths.len() && lengths.iter().all(|&l| l <= longest) =>
+            (columns, Some(longest * columns)),
+        shape => return Err(InkError::Schema(format!("points of shape {:?} do not match the lengths", shape))),
+    };
+    if columns < 2 {
+        return Err(InkError::Schema(String::from("points need at least x and y columns")));
+    }
+
+    let mut start = 0;
+    let mut sketches = vec![];
+    for (i, &length) in lengths.iter().enumerate() {
+        let offset = stride.map_or(start, |stride| i * stride);
+        let stroke_lengths = strokes.as_ref().map(|strokes| strokes[i].as_slice());
+        sketches.push(from_rows(&values[offset..offset + length * columns], columns, stroke_lengths));
+        start += length * columns;
+    }
+    Ok(sketches)
+}
+
+/// Points matrix of a sketch with the columns `COLUMNS`
+pub fn points(sketch: &Sketch) -> NpyArray {
+    let mut data = vec![];
+    for (i, stroke) in sketch.strokes.iter().enumerate() {
+        let has_time = stroke.timestamp.len() == stroke.len();
+        let has_pressure = stroke.pressure.len() == stroke.len();
+        for j in 0..stroke.len() {
+            data.push(stroke.x[j]);
+            data.push(stroke.y.get(j).copied().unwrap_or(f64::NAN));
+            data.push(if has_time { stroke.timestamp[j] as f64 } else { f64::NAN });
+            data.push(if has_pressure { stroke.pressure[j] } else { f64::NAN });
+            data.push(i as f64);
+        }
+    }
+    NpyArray { shape: vec![data.len() / COLUMNS.len(), COLUMNS.len()], data: NpyData::Float(data) }
+}
+
+/// Splits the stroke lengths of a collection by sketch, checking them against the point counts of the sketches
+fn split_strokes(lengths: &[usize], strokes: Vec<usize>, stroke_lengths: Vec<usize>) -> Result<Vec<Vec<usize>>, InkError> {
+    let mismatch = || InkError::Schema(String::from("`strokes` and `stroke_lengths` do not match the lengths"));
+    if strokes.len() != lengths.len() {
+        return Err(mismatch());
+    }
+    let mut remaining = stroke_lengths.as_slice();
+    let mut split = Vec::with_capacity(strokes.len());
+    for (&count, &length) in strokes.iter().zip(lengths.iter()) {
+        if count > remaining.len() {
+            return Err(mismatch());
+        }
+        let (sketch, rest) = remaining.split_at(count);
+        if sketch.iter().try_fold(0usize, |total, &l| total.checked_add(l)) != Some(length) {
+            return Err(mismatch());
+        }
+        split.push(sketch.to_vec());
+        remaining = rest;
+    }
+    if !remaining.is_empty() {
+        return Err(mismatch());
+    }
+    Ok(split)
+}
+
+/// Groups rows into strokes of the given lengths or else by their stroke column, timestamps and pressure are
+/// kept if none of a stroke is NaN
+fn from_rows(values: &[f64], columns: usize, stroke_lengths: Option<&[usize]>) -> Sketch {
+    let rows: Vec<&[f64]> = values.chunks(columns).collect();
+    let mut groups = vec![];
+    match stroke_lengths {
+        Some(stroke_lengths) => {
+            let mut start = 0;
+            for &length in stroke_lengths {
+                groups.push(&rows[start..start + length]);
+                start += length;
+            }
+        }
+        None => {
+            let id = |row: &[f64]| row.get(4).map(|v| v.to_bits());
+            let mut start = 0;
+            for end in 1..=rows.len() {
+                if end == rows.len() || id(rows[end]) != id(rows[start]) {
+                    groups.push(&rows[start..end]);
+                    start = end;
+                }
+            }
+        }
+    }
+
+    let column = |row: &[f64], index: usize| row.get(index).copied().unwrap_or(f64::NAN);
+    let mut sketch = Sketch::new(vec![]);
+    for group in groups {
+        let x = group.iter().map(|row| row[0]).collect();
+        let y = group.iter().map(|row| row[1]).collect();
+        let timestamp = if group.iter().all(|row| !column(row, 2).is_nan()) {
+            group.iter().map(|row| column(row, 2).max(0.).round() as u64).collect()
+        } else {
+            vec![]
+        };
+        let pressure = if group.iter().all(|row| !column(row, 3).is_nan()) {
+            group.iter().map(|row| column(row, 3)).collect()
+        } else {
+            vec![]
+        };
+        sketch.add_stroke(Stroke::new(x, y, timestamp, pressure));
+    }
+    sketch
+}
+
+/// Encodes an array as `.npy` version 1.0, floats as `<f8` and integers as `<i8`
+pub fn write_npy(array: &NpyArray) -> Vec<u8> {
+    let descr = match array.data {
+        NpyData::Float(_) => "<f8",
+        NpyData::Integer(_) => "<i8",
+    };
+    let shape = match array.shape.as_slice() {
+        [length] => format!("({},)", length),
+        shape => format!("({})", shape.iter().map(|s| s.to_string()).collect::<Vec<String>>().join(", ")),
+    };
+    let mut header = format!("{{'descr': '{}', 'fortran_order': False, 'shape': {}, }}", descr, shape);
+    // the data starts at a multiple of 64 bytes, the header ends with a newline
+    let unpadded = MAGIC.len() + 4 + header.len() + 1;
+    header.push_str(&" ".repeat((64 - unpadded % 64) % 64));
+    header.push('\n');
+
+    let mut out = MAGIC.to_vec();
+    out.extend_from_slice(&[1, 0]);
+    out.extend_from_slice(&(header.len() as u16).to_le_bytes());
+    out.extend_from_slice(header.as_bytes());
+    match &array.data {
+        NpyData::Float(values) => values.iter().for_each(|v| out.extend_from_slice(&v.to_le_bytes())),
+        NpyData::Integer(values) => values.iter().for_each(|v| out.extend_from_slice(&v.to_le_bytes())),
+    }
+    out
+}
+
+/// Decodes a `.npy` array of booleans, integers or floats up to 8 bytes in C order
+pub fn read_npy(bytes: &[u8]) -> Result<NpyArray, InkError> {
+    if !bytes.starts_with(MAGIC) || bytes.len() < MAGIC.len() + 4 {
+        return Err(InkError::Schema(String::from("input is not a .npy file")));
+    }
+    let (header_length, header_start) = match bytes[MAGIC.len()] {
+        1 => (u16::from_le_bytes([bytes[8], bytes[9]]) as usize, 10usize),
+        2 | 3 if bytes.len() >= 12 => (u32::from_le_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]) as usize, 12),
+        version => return Err(InkError::Schema(format!("unsupported .npy version {}", version))),
+    };
+    let header_end = header_start.checked_add(header_length)
+        .ok_or_else(|| InkError::Schema(String::from("invalid .npy header length")))?;
+    let header = bytes.get(header_start..header_end)
+        .and_then(|h| std::str::from_utf8(h).ok())
+        .ok_or_else(|| InkError::Schema(String::from("truncated .npy header")))?;
+
+    let descr = header_value(header, "descr")
+        .map(|d| d.trim_matches(|c| c == '\'' || c == '"'))
+        .ok_or_else(|| InkError::Schema(String::from(".npy header without `descr`")))?;
+    if header_value(header, "fortran_order") == Some("True") {
+        return Err(InkError::Schema(String::from("Fortran ordered arrays are not supported")));
+    }
+    let shape = header_value(header, "shape")
+        .ok_or_else(|| InkError::Schema(String::from(".npy header without `shape`")))?
+        .trim_matches(|c| c == '(' || c == ')')
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse::<usize>().map_err(|_| InkError::Schema(format!("invalid .npy shape `{}`", s))))
+        .collect::<Result<Vec<usize>, InkError>>()?;
+
+    let count = shape.iter().try_fold(1usize, |count, &length| count.checked_mul(length))
+        .ok_or_else(|| InkError::Schema(format!("too many elements in .npy shape {:?}", shape)))?;
+    let big_endian = descr.starts_with('>');
+    let kind = descr.get(1..).unwrap_or_default();
+    let size: usize = kind.get(1..).and_then(|s| s.parse().ok()).unwrap_or(0);
+    let data = &bytes[header_end..];
+    if size == 0 || count.checked_mul(size).map_or(true, |length| data.len() < length) {
+        return Err(InkError::Schema(format!("invalid or truncated .npy data of type `{}`", descr)));
+    }
+
+    let element = |i: usize| {
+        let mut buffer = [0u8; 8];
+        let chunk = &data[i * size..(i + 1) * size];
+        if big_endian {
+            buffer[8 - size..].copy_from_slice(chunk);
+            buffer.reverse();
+        } else {
+            buffer[..size].copy_from_slice(chunk);
+        }
+        buffer
+    };
+    let sign_extend = |buffer: [u8; 8]| {
+        let shift = 64 - 8 * size as u32;
+        (i64::from_le_bytes(buffer) << shift) >> shift
+    };
+
+    let data = match kind {
+        "f8" => NpyData::Float((0..count).map(|i| f64::from_le_bytes(element(i))).collect()),
+        "f4" => NpyData::Float((0..count).map(|i| {
+            let b = element(i);
+            f32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64
+        }).collect()),
+        "i1" | "i2" | "i4" | "i8" => NpyData::Integer((0..count).map(|i| sign_extend(element(i))).collect()),
+        "u1" | "u2" | "u4" | "u8" | "b1" => NpyData::Integer((0..count).map(|i| u64::from_le_bytes(element(i)) as i64).collect()),
+        _ => return Err(InkError::Schema(format!("unsupported .npy type `{}`", descr))),
+    };
+    Ok(NpyArray { shape, data })
+}
+
+/// Raw text of a value in the header dictionary, e.g. `(3, 5)` for `shape`
+fn header_value<'a>(header: &'a str, key: &str) -> Option<&'a str> {
+    let start = header.find(&format!("'{}'", key))? + key.len() + 2;
+    let rest = header[start..].trim_start().strip_prefix(':')?.trim_start();
+    let end = if rest.starts_with('(') {
+        rest.find(')')? + 1
+    } else {
+        rest.find(',').unwrap_or(rest.len())
+    };
+    Some(rest[..end].trim())
+}
+
+/// Bundles named arrays into an uncompressed `.npz` archive, as written by `numpy.savez`
+pub fn write_npz(arrays: &[(&str, &NpyArray)]) -> Result<Vec<u8>, InkError> {
+    let mut writer = ZipWriter::new(Cursor::new(vec![]));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+    for (name, array) in arrays.iter() {
+        writer.start_file(format!("{}.npy", name), options)?;
+        writer.write_all(&write_npy(array))?;
+    }
+    Ok(writer.finish()?.into_inner())
+}
+
+/// Reads all arrays of an `.npz` archive, compressed archives as written by `numpy.savez_compressed` included
+pub fn read_npz(serialized_bytes: Vec<u8>) -> Result<Vec<(String, NpyArray)>, InkError> {
+    let mut archive = ZipArchive::new(Cursor::new(serialized_bytes))?;
+    let mut arrays = vec![];
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i)?;
+        let name = file.name().trim_end_matches(".npy").to_string();
+        let mut bytes = vec![];
+        file.read_to_end(&mut bytes)?;
+        arrays.push((name, read_npy(&bytes)?));
+    }
+    Ok(arrays)
+}
@@ -0,0 +1,229 @@
+//! Google Quick, Draw! ndjson files, one drawing per line
+//!
+//! Raw drawings store every stroke as `[[x…], [y…], [t…]]` with times in milliseconds, simplified drawings
+//! as `[[x…], [y…]]` with coordinates between 0 and 255.
+
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use std::path::Path;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::error::InkError;
+use crate::serialization::compression::{self, CompressedWriter};
+use crate::serialization::jsonl_serializer::{line_error, LineReader};
+use crate::simplification::SimplificationAlgorithm;
+use crate::sketch::Sketch;
+use crate::stroke::Stroke;
+
+/// Top-level fields written ahead of the remaining `meta` entries, in the order of the dataset
+const FIELDS: &[&str] = &["key_id", "word", "countrycode", "timestamp", "recognized"];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QuickDrawFormat {
+    /// Original coordinates with timestamps
+    Raw,
+    /// Aligned to the top left, scaled to 0–255, resampled with 1 pixel spacing and simplified with
+    /// Ramer–Douglas–Peucker at a tolerance of 2, as in the dataset's simplified files
+    Simplified,
+}
+
+#[derive(Deserialize)]
+struct Drawing {
+    drawing: Vec<Vec<Vec<f64>>>,
+    #[serde(flatten)]
+    meta: HashMap<String, Value>,
+}
+
+/// Reads a single drawing, all fields besides `drawing` end up in the sketch's `meta`
+pub fn loads_sketch(serialized_string: String) -> Result<Sketch, InkError> {
+    let drawing: Drawing = serde_json::from_str(serialized_string.as_str())?;
+    to_sketch(drawing)
+}
+
+fn to_sketch(drawing: Drawing) -> Result<Sketch, InkError> {
+    let mut strokes = vec![];
+    for (i, arrays) in drawing.drawing.into_iter().enumerate() {
+        let mut arrays = arrays.into_iter();
+        let (x, y) = match (arrays.next(), arrays.next()) {
+            (Some(x), Some(y)) => (x, y),
+            _ => return Err(InkError::Schema(format!("stroke {} has no x and y arrays", i))),
+        };
+        let timestamp: Vec<u64> = arrays.next().unwrap_or_default().iter().map(|&t| t.max(0.).round() as u64).collect();
+        if y.len() != x.len() || !(timestamp.is_empty() || timestamp.len() == x.len()) {
+            return Err(InkError::Schema(format!(
+                "stroke {} has {} x, {} y and {} t values", i, x.len(), y.len(), timestamp.len()
+            )));
+        }
+        strokes.push(Stroke::new(x, y, timestamp, vec![]));
+    }
+
+    let mut sketch = Sketch::new(strokes);
+    sketch.meta = drawing.meta;
+    Ok(sketch)
+}
+
+/// Writes a drawing as a single line without line break
+pub fn dumps_sketch(sketch: &Sketch, format: QuickDrawFormat) -> String {
+    let simplified;
+    let sketch = match format {
+        QuickDrawFormat::Raw => sketch,
+        QuickDrawFormat::Simplified => {
+            simplified = simplify(sketch);
+            &simplified
+        }
+    };
+
+    let mut fields: Vec<(&str, &Value)> = FIELDS.iter()
+        .filter_map(|&key| sketch.meta.get(key).map(|value| (key, value)))
+        .collect();
+    let mut other: Vec<(&str, &Value)> = sketch.meta.iter()
+        .filter(|(key, _)| !FIELDS.contains(&key.as_str()) && key.as_str() != "drawing")
+        .map(|(key, value)| (key.as_str(), value))
+        .collect();
+    other.sort_by_key(|&(key, _)| key);
+    fields.extend(other);
+
+    let mut out = String::from("{");
+    for (key, value) in fields {
+        out.push_str(&format!("{}:{},", Value::from(key), value));
+    }
+    out.push_str("\"drawing\":[");
+    for (i, stroke) in sketch.strokes.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        out.push_str(&format!("[{},{}", numbers(&stroke.x), numbers(&stroke.y)));
+        if format == QuickDrawFormat::Raw && !stroke.timestamp.is_empty() {
+            let t: Vec<String> = stroke.timestamp.iter().map(|t| t.to_string()).collect();
+            out.push_str(&format!(",[{}]", t.join(",")));
+        }
+        out.push(']');
+    }
+    out.push_str("]}");
+    out
+}
+
+/// Integer-valued coordinates are written without fraction, as in the dataset
+fn numbers(values: &[f64]) -> String {
+    let values: Vec<String> = values.iter()
+        .map(|&v| if v.fract() == 0. && v.abs() < 1e15 { format!("{}", v as i64) } else { Value::from(v).to_string() })
+        .collect();
+    format!("[{}]", values.join(","))
+}
+
+/// Applies the preprocessing of the simplified dataset files to a copy of the sketch
+pub fn simplify(sketch: &Sketch) -> Sketch {
+    let mut simplified = sketch.clone();
+    simplified.retain_strokes(|stroke| !stroke.is_empty());
+    if simplified.is_empty() {
+        return simplified;
+    }
+
+    simplified.offset(Some(-simplified.x_min()), Some(-simplified.y_min()));
+    let extent = simplified.x_max().max(simplified.y_max());
+    if extent > 0. {
+        simplified.scale(Some(255. / extent), Some(255. / extent));
+    }
+    simplified.resample_by_distance(1.);
+    simplified.simplify(2., SimplificationAlgorithm::RamerDouglasPeucker);
+    for stroke in simplified.strokes.iter_mut() {
+        stroke.timestamp.clear();
+        stroke.pressure.clear();
+        stroke.channels.clear();
+        for x in stroke.x.iter_mut() {
+            *x = x.round();
+        }
+        for y in stroke.y.iter_mut() {
+            *y = y.round();
+        }
+    }
+    simplified
+}
+
+/// Iterator over the drawings of an ndjson stream, see `jsonl_serializer::SketchReader` for the error handling
+pub struct QuickDrawReader<R: BufRead> {
+    lines: LineReader<R>,
+}
+
+impl<R: BufRead> QuickDrawReader<R> {
+    pub fn new(reader: R) -> QuickDrawReader<R> {
+        QuickDrawReader { lines: LineReader::new(reader) }
+    }
+
+    /// Line number of the last line read, 1-based
+    pub fn line_number(&self) -> usize {
+        self.lines.line_number()
+    }
+}
+
+fn parse_line(line: &str, line_number: usize) -> Result<Sketch, InkError> {
+    let drawing = serde_json::from_str(line).map_err(|e| line_error(e, line_number))?;
+    to_sketch(drawing).map_err(|e| match e {
+        InkError::Schema(message) => InkError::Schema(format!("line {}: {}", line_number, message)),
+        e => e,
+    })
+}
+
+impl<R: BufRead> Iterator for QuickDrawReader<R> {
+    type Item = Result<Sketch, InkError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.lines.next_with(parse_line)
+    }
+}
+
+/// Writes drawings one per line in the given format
+pub struct QuickDrawWriter<W: Write> {
+    writer: W,
+    format: QuickDrawFormat,
+}
+
+impl<W: Write> QuickDrawWriter<W> {
+    pub fn new(writer: W, format: QuickDrawFormat) -> QuickDrawWriter<W> {
+        QuickDrawWriter { writer, format }
+    }
+
+    pub fn write_sketch(&mut self, sketch: &Sketch) -> Result<(), InkError> {
+        self.writer.write_all(dumps_sketch(sketch, self.format).as_bytes())?;
+        self.writer.write_all(b"\n")?;
+        Ok(())
+    }
+
+    /// Flushes and returns the underlying writer
+    pub fn into_inner(mut self) -> Result<W, InkError> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Opens an ndjson file for reading drawing by drawing, raw and simplified drawings are told apart per stroke
+pub fn read_sketches(file_path: impl AsRef<Path>) -> Result<QuickDrawReader<Box<dyn BufRead>>, InkError> {
+    Ok(QuickDrawReader::new(compression::open(file_path)?))
+}
+
+/// Creates or truncates an ndjson file for writing, see `compression::create`
+pub fn create_sketches(file_path: impl AsRef<Path>, format: QuickDrawFormat) -> Result<QuickDrawWriter<CompressedWriter>, InkError> {
+    Ok(QuickDrawWriter::new(compression::create(file_path)?, format))
+}
+
+pub fn dump_sketches(sketches: &[Sketch], file_path: impl AsRef<Path>, format: QuickDrawFormat) -> Result<(), InkError> {
+    let mut writer = create_sketches(file_path, format)?;
+    for sketch in sketches.iter() {
+        writer.write_sketch(sketch)?;
+    }
+    writer.into_inner()?.finish()
+}
+
+pub fn dumps_sketches(sketches: &[Sketch], format: QuickDrawFormat) -> String {
+    sketches.iter().map(|sketch| dumps_sketch(sketch, format) + "\n").collect()
+}
+
+pub fn load_sketches(file_path: impl AsRef<Path>) -> Result<Vec<Sketch>, InkError> {
+    read_sketches(file_path)?.collect()
+}
+
+pub fn loads_sketches(serialized_string: String) -> Result<Vec<Sketch>, InkError> {
+    QuickDrawReader::new(serialized_string.as_bytes()).collect()
+}
@@ -0,0 +1,184 @@
+use std::path::Path;
+
+use crate::error::InkError;
+use crate::serialization::{compression, escape_xml};
+use crate::sketch::Sketch;
+use crate::stroke::Stroke;
+use crate::style::StrokeStyle;
+use crate::units::YDirection;
+
+/// Rendering options for the SVG export
+#[derive(Clone, Debug)]
+pub struct SvgOptions {
+    /// Width of the canvas in pixels
+    pub width: f64,
+    /// Height of the canvas in pixels
+    pub height: f64,
+    /// Space around the sketch, in sketch coordinates
+    pub margin: f64,
+    /// Width of the strokes without a width in their style, in sketch coordinates
+    pub stroke_width: f64,
+    /// Color of the strokes without a color in their style
+    pub stroke_color: String,
+    /// Background fill, transparent if `None`
+    pub background_color: Option<String>,
+    /// Render strokes as filled outlines whose width follows the pressure channel
+    pub pressure_sensitive: bool,
+    /// Smallest width factor applied to a stroke when rendering with pressure
+    pub min_width_factor: f64,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        SvgOptions {
+            width: 500.,
+            height: 500.,
+            margin: 10.,
+            stroke_width: 2.,
+            stroke_color: String::from("black"),
+            background_color: None,
+            pressure_sensitive: false,
+            min_width_factor: 0.2,
+        }
+    }
+}
+
+pub fn dump_sketch(sketch: &Sketch, options: &SvgOptions, file_path: impl AsRef<Path>) -> Result<(), InkError> {
+    let svg_string = dumps_sketch(sketch, options);
+    compression::write(file_path, svg_string)
+}
+
+/// Renders a sketch as an SVG document with one path per stroke
+///
+/// The view box spans at least the widest stroke in each direction, so that single dots stay visible, and
+/// sketches whose coordinate system has y growing upwards are flipped to appear upright.
+pub fn dumps_sketch(sketch: &Sketch, options: &SvgOptions) -> String {
+    let (x_min, y_min, width, height) = if sketch.strokes.iter().any(|s| !s.is_empty()) {
+        (sketch.x_min(), sketch.y_min(), sketch.x_max() - sketch.x_min(), sketch.y_max() - sketch.y_min())
+    } else {
+        (0., 0., 0., 0.)
+    };
+    let pressure_max = sketch.pressure_max();
+
+    let default_style = StrokeStyle {
+        color: Some(options.stroke_color.clone()),
+        width: Some(options.stroke_width),
+        ..StrokeStyle::default()
+    };
+    let styles: Vec<StrokeStyle> = (0..sketch.strokes.len()).map(|i| sketch.stroke_style(i).inherit(&default_style)).collect();
+    let min_extent = styles.iter().filter_map(|style| style.width).fold(1., f64::max);
+    let view_width = (width + 2. * options.margin).max(min_extent);
+    let view_height = (height + 2. * options.margin).max(min_extent);
+    let view_x = x_min - (view_width - width) / 2.;
+    let view_y = y_min - (view_height - height) / 2.;
+
+    let mut out = String::new();
+    out.push_str(&format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"{} {} {} {}\">\n",
+        options.width, options.height, view_x, view_y, view_width, view_height
+    ));
+    if let Some(background_color) = &options.background_color {
+        out.push_str(&format!(
+            "  <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>\n",
+            view_x, view_y, view_width, view_height, escape_xml(background_color)
+        ));
+    }
+
+    let flipped = sketch.coordinate_system.y_direction == YDirection::Up;
+    if flipped {
+        // mirrors about the vertical center of the sketch, which keeps it inside the view box
+        out.push_str(&format!("  <g transform=\"matrix(1 0 0 -1 0 {})\">\n", 2. * y_min + height));
+    }
+    for (i, stroke) in sketch.strokes.iter().enumerate().filter(|(_, s)| !s.is_empty()) {
+        let style = &styles[i];
+        let use_pressure = options.pressure_sensitive
+            && stroke.pressure.len() == stroke.len()
+            && pressure_max > 0.;
+        if use_pressure {
+            write_outline(&mut out, stroke, style, options, pressure_max);
+        } else {
+            write_path(&mut out, stroke, style);
+        }
+    }
+    if flipped {
+        out.push_str("  </g>\n");
+    }
+
+    out.push_str("</svg>\n");
+    out
+}
+
+/// ` opacity="…"` attribute for styles with an opacity, empty otherwise
+fn opacity_attribute(style: &StrokeStyle) -> String {
+    match style.opacity {
+        Some(opacity) => format!(" opacity=\"{}\"", opacity),
+        None => String::new(),
+    }
+}
+
+fn write_path(out: &mut String, stroke: &Stroke, style: &StrokeStyle) {
+    let color = escape_xml(style.color.as_deref().unwrap_or_default());
+    let width = style.width.unwrap_or_default();
+    if stroke.len() == 1 {
+        out.push_str(&format!(
+            "  <circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\"{}/>\n",
+            stroke.x[0], stroke.y[0], width / 2., color, opacity_attribute(style)
+        ));
+        return;
+    }
+
+    let mut d = format!("M{} {}", stroke.x[0], stroke.y[0]);
+    for i in 1..stroke.len() {
+        d.push_str(&format!(" L{} {}", stroke.x[i], stroke.y[i]));
+    }
+    out.push_str(&format!(
+        "  <path d=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\" stroke-linecap=\"round\" stroke-linejoin=\"round\"{}/>\n",
+        d, color, width, opacity_attribute(style)
+    ));
+}
+
+/// Writes a stroke as a filled polygon, offsetting each point along its normal by half the local width
+fn write_outline(out: &mut String, stroke: &Stroke, style: &StrokeStyle, options: &SvgOptions, pressure_max: f64) {
+    let color = escape_xml(style.color.as_deref().unwrap_or_default());
+    let width = style.width.unwrap_or_default();
+    let half_width = |i: usize| {
+        let factor = (stroke.pressure[i] / pressure_max).max(options.min_width_factor).min(1.);
+        width * factor / 2.
+    };
+
+    if stroke.len() == 1 {
+        out.push_str(&format!(
+            "  <circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\"{}/>\n",
+            stroke.x[0], stroke.y[0], half_width(0), color, opacity_attribute(style)
+        ));
+        return;
+    }
+
+    let n = stroke.len();
+    let mut left = Vec::with_capacity(n);
+    let mut right = Vec::with_capacity(n);
+    for i in 0..n {
+        let previous = if i > 0 { i - 1 } else { i };
+        let next = if i + 1 < n { i + 1 } else { i };
+        let dx = stroke.x[next] - stroke.x[previous];
+        let dy = stroke.y[next] - stroke.y[previous];
+        let length = (dx * dx + dy * dy).sqrt();
+        let (nx, ny) = if length > 0. { (-dy / length, dx / length) } else { (0., 0.) };
+        let w = half_width(i);
+        left.push((stroke.x[i] + nx * w, stroke.y[i] + ny * w));
+        right.push((stroke.x[i] - nx * w, stroke.y[i] - ny * w));
+    }
+
+    let mut d = format!("M{} {}", left[0].0, left[0].1);
+    for point in left.iter().skip(1) {
+        d.push_str(&format!(" L{} {}", point.0, point.1));
+    }
+    for point in right.iter().rev() {
+        d.push_str(&format!(" L{} {}", point.0, point.1));
+    }
+    d.push_str(" Z");
+    out.push_str(&format!(
+        "  <path d=\"{}\" fill=\"{}\" stroke=\"{}\" stroke-width=\"{}\" stroke-linejoin=\"round\"{}/>\n",
+        d, color, color, width * options.min_width_factor, opacity_attribute(style)
+    ));
+}
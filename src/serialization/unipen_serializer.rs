@@ -0,0 +1,310 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde_json::{json, Value};
+
+use crate::annotation::TraceGroup;
+use crate::error::InkError;
+use crate::serialization::compression;
+use crate::sketch::Sketch;
+use crate::stroke::Stroke;
+use crate::units::{CoordinateSystem, Unit};
+
+/// Directives whose content is copied verbatim into the sketch's `meta`, keyed by their lowercase name
+const HEADER_DIRECTIVES: &[&str] = &[
+    "VERSION", "DATA_SOURCE", "DATA_ID", "DATA_CONTACT", "DATA_INFO", "SETUP", "PAD", "ALPHABET", "LEXICON",
+    "HIERARCHY", "WRITER_ID", "COUNTRY", "HAND", "AGE", "SEX", "SKILL", "STYLE", "STYLE_INFO", "RECORDING_DATE",
+    "RECORDING_STYLE", "X_DIM", "Y_DIM", "H_LINE", "V_LINE", "X_POINTS_PER_INCH", "Y_POINTS_PER_INCH",
+    "X_POINTS_PER_MM", "Y_POINTS_PER_MM",
+];
+
+pub fn load_sketch(file_path: impl AsRef<Path>) -> Result<Sketch, InkError> {
+    let contents = compression::read_to_string(file_path)?;
+    loads_sketch(contents)
+}
+
+/// Reads a UNIPEN file into a sketch
+///
+/// Every `.PEN_DOWN` component becomes a stroke, `.PEN_UP` components only advance the time. The `.COORD`
+/// channels X, Y, P and T are mapped onto `x`, `y`, `pressure` and `timestamp`, without a T channel timestamps
+/// in milliseconds are derived from `.POINTS_PER_SECOND` and `.DT`. `.SEGMENT` directives become groups named
+/// after their lowercase hierarchy level, with the quoted text as label and the quality as `quality` attribute;
+/// their component numbers count `.PEN_DOWN` and `.PEN_UP` components alike and pen-up components are left out of
+/// the groups. Header directives end up in `meta`, and the
+/// resolution from `.X_POINTS_PER_INCH` or `.X_POINTS_PER_MM` in the device coordinate system of the sketch.
+///
+/// Directive names start with a letter, are matched case-insensitively and may span several lines, `.COMMENT`
+/// and unknown directives are skipped.
+pub fn loads_sketch(serialized_string: String) -> Result<Sketch, InkError> {
+    let mut reader = UnipenReader::default();
+    for directive in split_directives(&serialized_string) {
+        reader.read_directive(&directive)?;
+    }
+    reader.into_sketch()
+}
+
+struct Directive {
+    name: String,
+    content: String,
+    line: usize,
+}
+
+/// Splits the file into directives, each holding the text up to the next line starting with `.`
+fn split_directives(text: &str) -> Vec<Directive> {
+    let mut directives: Vec<Directive> = vec![];
+    for (i, line) in text.lines().enumerate() {
+        let trimmed = line.trim();
+        let directive = trimmed.strip_prefix('.').filter(|rest| rest.starts_with(|c: char| c.is_ascii_alphabetic()));
+        if let Some(rest) = directive {
+            let (name, content) = match rest.find(char::is_whitespace) {
+                Some(position) => (&rest[..position], rest[position..].trim()),
+                None => (rest, ""),
+            };
+            directives.push(Directive { name: name.to_ascii_uppercase(), content: String::from(content), line: i + 1 });
+        } else if let Some(directive) = directives.last_mut() {
+            if !trimmed.is_empty() {
+                directive.content.push('\n');
+                directive.content.push_str(trimmed);
+            }
+        }
+    }
+    directives
+}
+
+struct Segment {
+    level: String,
+    delineation: String,
+    quality: Option<String>,
+    label: Option<String>,
+    line: usize,
+}
+
+struct UnipenReader {
+    meta: HashMap<String, Value>,
+    coord: Vec<String>,
+    points_per_second: Option<f64>,
+    strokes: Vec<Stroke>,
+    /// Stroke index of every component in file order, `None` for pen-up components
+    components: Vec<Option<usize>>,
+    segments: Vec<Segment>,
+    time: f64,
+}
+
+impl Default for UnipenReader {
+    fn default() -> Self {
+        UnipenReader {
+            meta: HashMap::new(),
+            coord: vec![String::from("X"), String::from("Y")],
+            points_per_second: None,
+            strokes: vec![],
+            components: vec![],
+            segments: vec![],
+            time: 0.,
+        }
+    }
+}
+
+impl UnipenReader {
+    fn read_directive(&mut self, directive: &Directive) -> Result<(), InkError> {
+        let name = directive.name.as_str();
+        match name {
+            "PEN_DOWN" | "PEN_UP" => {
+                let columns = self.read_points(directive)?;
+                if name == "PEN_DOWN" {
+                    let stroke = self.build_stroke(columns);
+                    self.components.push(Some(self.strokes.len()));
+                    self.strokes.push(stroke);
+                } else {
+                    self.components.push(None);
+                    self.advance_time(columns.first().map_or(0, |c| c.len()));
+                }
+            }
+            "DT" => {
+                self.time += parse_number(directive.content.trim(), directive.line)?;
+            }
+            "SEGMENT" => self.segments.push(parse_segment(directive)?),
+            "COORD" => {
+                self.coord = directive.content.split_whitespace().map(|c| c.to_ascii_uppercase()).collect();
+                self.meta.insert(String::from("coord"), json!(directive.content));
+            }
+            "POINTS_PER_SECOND" => {
+                self.points_per_second = Some(parse_number(directive.content.trim(), directive.line)?);
+                self.meta.insert(String::from("points_per_second"), json!(self.points_per_second));
+            }
+            _ if HEADER_DIRECTIVES.contains(&name) => {
+                let value = match directive.content.trim().parse::<f64>() {
+                    Ok(number) => json!(number),
+                    Err(_) => json!(directive.content.trim().trim_matches('"')),
+                };
+                self.meta.insert(name.to_ascii_lowercase(), value);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Values of the component as one column per `.COORD` channel
+    fn read_points(&self, directive: &Directive) -> Result<Vec<Vec<f64>>, InkError> {
+        let channel_count = self.coord.len().max(1);
+        let values = directive.content.split_whitespace()
+            .map(|value| parse_number(value, directive.line))
+            .collect::<Result<Vec<f64>, InkError>>()?;
+        if values.len() % channel_count != 0 {
+            return Err(InkError::Schema(format!(
+                "line {}: component has {} values, expected a multiple of {}", directive.line, values.len(), channel_count
+            )));
+        }
+
+        let mut columns = vec![vec![]; channel_count];
+        for point in values.chunks(channel_count) {
+            for (column, &value) in columns.iter_mut().zip(point) {
+                column.push(value);
+            }
+        }
+        Ok(columns)
+    }
+
+    fn channel(&self, columns: &mut [Vec<f64>], name: &str) -> Option<Vec<f64>> {
+        let index = self.coord.iter().position(|c| c == name)?;
+        Some(std::mem::take(&mut columns[index]))
+    }
+
+    fn build_stroke(&mut self, mut columns: Vec<Vec<f64>>) -> Stroke {
+        let x = self.channel(&mut columns, "X").unwrap_or_default();
+        let y = self.channel(&mut columns, "Y").unwrap_or_default();
+        let pressure = self.channel(&mut columns, "P").unwrap_or_default();
+        let timestamp = match self.channel(&mut columns, "T") {
+            Some(t) => t.iter().map(|&t| t.max(0.).round() as u64).collect(),
+            None if self.points_per_second.is_some() => {
+                let start = self.time;
+                self.advance_time(x.len());
+                let interval = self.interval();
+                (0..x.len()).map(|i| (start + i as f64 * interval).round() as u64).collect()
+            }
+            None => vec![],
+        };
+        Stroke::new(x, y, timestamp, pressure)
+    }
+
+    /// Milliseconds between two samples
+    fn interval(&self) -> f64 {
+        match self.points_per_second {
+            Some(rate) if rate > 0. => 1000. / rate,
+            _ => 0.,
+        }
+    }
+
+    fn advance_time(&mut self, samples: usize) {
+        self.time += samples as f64 * self.interval();
+    }
+
+    fn into_sketch(self) -> Result<Sketch, InkError> {
+        let mut sketch = Sketch::new(self.strokes);
+        let mut groups = vec![];
+        for segment in self.segments.iter() {
+            groups.push(build_group(segment, &sketch, &self.components)?);
+        }
+        sketch.groups = groups;
+
+        let resolution = match (self.meta.get("x_points_per_inch"), self.meta.get("x_points_per_mm")) {
+            (Some(Value::Number(dpi)), _) => dpi.as_f64(),
+            (_, Some(Value::Number(per_mm))) => per_mm.as_f64().map(|r| r * 25.4),
+            _ => None,
+        };
+        if resolution.is_some() {
+            sketch.coordinate_system = CoordinateSystem::new(Unit::Device, resolution);
+        }
+        sketch.meta = self.meta;
+        Ok(sketch)
+    }
+}
+
+fn parse_number(value: &str, line: usize) -> Result<f64, InkError> {
+    value.parse().map_err(|_| InkError::Schema(format!("line {}: invalid number `{}`", line, value)))
+}
+
+/// Parses `.SEGMENT <level> <delineation> [<quality>] ["<label>"]`
+fn parse_segment(directive: &Directive) -> Result<Segment, InkError> {
+    let content = directive.content.replace('\n', " ");
+    let (fields, label) = match content.find('"') {
+        Some(start) => {
+            let label = content[start + 1..].trim_end();
+            (&content[..start], Some(String::from(label.strip_suffix('"').unwrap_or(label))))
+        }
+        None => (content.as_str(), None),
+    };
+
+    let mut fields = fields.split_whitespace();
+    let level = match fields.next() {
+        Some(level) => level.to_ascii_lowercase(),
+        None => return Err(InkError::Schema(format!("line {}: segment without hierarchy level", directive.line))),
+    };
+    let delineation = String::from(fields.next().unwrap_or(""));
+    let quality = fields.next().map(String::from);
+    // unquoted labels follow the quality
+    let label = label.or_else(|| {
+        let rest: Vec<&str> = fields.collect();
+        if rest.is_empty() { None } else { Some(rest.join(" ")) }
+    });
+
+    Ok(Segment { level, delineation, quality, label, line: directive.line })
+}
+
+/// Resolves a delineation such as `0-3`, `4,6` or `2:10-3:5` into stroke references
+///
+/// Component numbers are resolved through `components` into stroke indices, point numbers are inclusive in
+/// UNIPEN and translated into half-open ranges.
+fn build_group(segment: &Segment, sketch: &Sketch, components: &[Option<usize>]) -> Result<TraceGroup, InkError> {
+    let mut group = TraceGroup::new(&segment.level);
+    group.label = segment.label.clone();
+    if let Some(quality) = &segment.quality {
+        group.attributes.insert(String::from("quality"), json!(quality));
+    }
+
+    let invalid = || InkError::Schema(format!("line {}: invalid segment delineation `{}`", segment.line, segment.delineation));
+    let parse_bound = |bound: &str| -> Result<(usize, Option<usize>), InkError> {
+        let mut parts = bound.trim().splitn(2, ':');
+        let component = parts.next().unwrap_or("").parse().map_err(|_| invalid())?;
+        let point = match parts.next() {
+            Some(point) => Some(point.parse().map_err(|_| invalid())?),
+            None => None,
+        };
+        if component >= components.len() {
+            return Err(InkError::Schema(format!("line {}: segment refers to missing component {}", segment.line, component)));
+        }
+        Ok((component, point))
+    };
+
+    for item in segment.delineation.split(',').filter(|i| !i.trim().is_empty()) {
+        let (start, end) = match item.find('-') {
+            Some(position) => (parse_bound(&item[..position])?, parse_bound(&item[position + 1..])?),
+            None => {
+                let bound = parse_bound(item)?;
+                (bound, bound)
+            }
+        };
+        if end.0 < start.0 {
+            return Err(invalid());
+        }
+
+        for (component, &stroke) in components.iter().enumerate().take(end.0 + 1).skip(start.0) {
+            let stroke = match stroke {
+                Some(stroke) => stroke,
+                None => continue,
+            };
+            let length = sketch.strokes[stroke].len();
+            let first = if component == start.0 { start.1 } else { None };
+            let last = if component == end.0 { end.1 } else { None };
+            match (first, last) {
+                (None, None) => group.add_stroke(stroke),
+                (first, last) => {
+                    let first = first.unwrap_or(0).min(length);
+                    let last = last.map_or(length, |l| (l + 1).min(length));
+                    group.add_stroke_range(stroke, first, last.max(first));
+                }
+            }
+        }
+    }
+
+    Ok(group)
+}
@@ -0,0 +1,101 @@
+/// Polyline simplification algorithms available for `Stroke::simplify`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SimplificationAlgorithm {
+    /// Drops points closer than the tolerance to the simplified polyline
+    RamerDouglasPeucker,
+    /// Drops points whose triangle with their neighbours has an area below the tolerance
+    VisvalingamWhyatt,
+}
+
+/// Returns for each point whether it is kept, first and last point are always kept
+pub(crate) fn simplify(x: &[f64], y: &[f64], tolerance: f64, algorithm: SimplificationAlgorithm) -> Vec<bool> {
+    match algorithm {
+        SimplificationAlgorithm::RamerDouglasPeucker => ramer_douglas_peucker(x, y, tolerance),
+        SimplificationAlgorithm::VisvalingamWhyatt => visvalingam_whyatt(x, y, tolerance),
+    }
+}
+
+fn ramer_douglas_peucker(x: &[f64], y: &[f64], tolerance: f64) -> Vec<bool> {
+    let n = x.len();
+    let mut keep = vec![false; n];
+    if n == 0 {
+        return keep;
+    }
+    keep[0] = true;
+    keep[n - 1] = true;
+
+    let mut ranges = vec![(0, n - 1)];
+    while let Some((start, end)) = ranges.pop() {
+        if end <= start + 1 {
+            continue;
+        }
+
+        let mut max_distance = 0.;
+        let mut index = start;
+        for i in (start + 1)..end {
+            let distance = segment_distance((x[i], y[i]), (x[start], y[start]), (x[end], y[end]));
+            if distance > max_distance {
+                max_distance = distance;
+                index = i;
+            }
+        }
+
+        if max_distance > tolerance {
+            keep[index] = true;
+            ranges.push((start, index));
+            ranges.push((index, end));
+        }
+    }
+
+    keep
+}
+
+fn visvalingam_whyatt(x: &[f64], y: &[f64], tolerance: f64) -> Vec<bool> {
+    let n = x.len();
+    let mut keep = vec![true; n];
+    if n < 3 {
+        return keep;
+    }
+
+    let mut alive: Vec<usize> = (0..n).collect();
+    let area = |a: usize, b: usize, c: usize| {
+        ((x[b] - x[a]) * (y[c] - y[a]) - (x[c] - x[a]) * (y[b] - y[a])).abs() / 2.
+    };
+    let mut areas: Vec<f64> = (1..n - 1).map(|i| area(i - 1, i, i + 1)).collect();
+
+    while alive.len() > 2 {
+        let (position, &min_area) = areas.iter()
+            .enumerate()
+            .min_by(|a, b| a.1.partial_cmp(b.1).unwrap_or(std::cmp::Ordering::Equal))
+            .unwrap();
+        if min_area >= tolerance {
+            break;
+        }
+
+        // `position` indexes the interior points, so the removed point is alive[position + 1]
+        keep[alive[position + 1]] = false;
+        alive.remove(position + 1);
+        areas.remove(position);
+
+        if position > 0 {
+            areas[position - 1] = area(alive[position - 1], alive[position], alive[position + 1]);
+        }
+        if position < areas.len() {
+            areas[position] = area(alive[position], alive[position + 1], alive[position + 2]);
+        }
+    }
+
+    keep
+}
+
+fn segment_distance(point: (f64, f64), start: (f64, f64), end: (f64, f64)) -> f64 {
+    let dx = end.0 - start.0;
+    let dy = end.1 - start.1;
+    let length_squared = dx * dx + dy * dy;
+    let t = if length_squared > 0. {
+        (((point.0 - start.0) * dx + (point.1 - start.1) * dy) / length_squared).clamp(0., 1.)
+    } else {
+        0.
+    };
+    ((point.0 - start.0 - t * dx).powi(2) + (point.1 - start.1 - t * dy).powi(2)).sqrt()
+}
@@ -7,18 +7,35 @@ use serde::de::{self, Deserializer, MapAccess, SeqAccess, Visitor};
 use serde_json::json;
 
 use super::stroke;
-
-#[derive(Debug, Serialize)]
+use crate::annotation::{StrokeReference, TraceGroup};
+use crate::error::InkError;
+use crate::point::Point;
+use crate::simplification::SimplificationAlgorithm;
+use crate::smoothing::SmoothingOptions;
+use crate::style::StrokeStyle;
+use crate::transform::AffineTransform;
+use crate::units::CoordinateSystem;
+use crate::validation::ValidationIssue;
+
+#[derive(Clone, Debug, Serialize)]
 pub struct Sketch {
     #[serde(rename = "type")]
     pub typ: String,
     pub meta: HashMap<String, serde_json::Value>,
     pub strokes: Vec<stroke::Stroke>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<TraceGroup>,
+    /// Default style of the strokes, see `Sketch::stroke_style`
+    #[serde(skip_serializing_if = "StrokeStyle::is_empty")]
+    pub style: StrokeStyle,
+    /// Unit, orientation and time unit of the coordinates, see `Sketch::convert_coordinate_system`
+    #[serde(skip_serializing_if = "CoordinateSystem::is_default")]
+    pub coordinate_system: CoordinateSystem,
 }
 
 impl Sketch {
     pub fn new(strokes: Vec<stroke::Stroke>) -> Sketch {
-        Sketch { typ: String::from("stroke"), meta: HashMap::new(), strokes }
+        Sketch { typ: String::from("stroke"), meta: HashMap::new(), strokes, groups: vec![], style: StrokeStyle::default(), coordinate_system: CoordinateSystem::default() }
     }
 
     pub fn strokes(&self) -> &Vec<stroke::Stroke> {
@@ -29,8 +46,109 @@ impl Sketch {
         self.strokes.push(stroke);
     }
 
+    /// Style of the stroke at `index` with unset attributes inherited from the sketch style
+    pub fn stroke_style(&self, index: usize) -> StrokeStyle {
+        self.strokes[index].style.inherit(&self.style)
+    }
+
+    /// Top-level groups of the annotation tree
+    pub fn groups(&self) -> &Vec<TraceGroup> {
+        &self.groups
+    }
+
+    pub fn add_group(&mut self, group: TraceGroup) {
+        self.groups.push(group);
+    }
+
+    /// Depth-first search for the first group with the given name
+    pub fn find_group(&self, name: &str) -> Option<&TraceGroup> {
+        self.groups.iter().find_map(|g| g.find(name))
+    }
+
+    /// Removes and returns the stroke at `index`, group references are updated accordingly
+    pub fn remove_stroke(&mut self, index: usize) -> stroke::Stroke {
+        let mapping: Vec<Option<usize>> = (0..self.strokes.len())
+            .map(|i| if i < index { Some(i) } else if i > index { Some(i - 1) } else { None })
+            .collect();
+        let stroke = self.strokes.remove(index);
+        self.remap_groups(&mapping);
+        stroke
+    }
+
+    /// Keeps only the strokes for which `f` returns true, group references are updated accordingly
+    pub fn retain_strokes<F>(&mut self, mut f: F) where F: FnMut(&stroke::Stroke) -> bool {
+        let mut next = 0;
+        let mapping: Vec<Option<usize>> = self.strokes.iter()
+            .map(|stroke| if f(stroke) { next += 1; Some(next - 1) } else { None })
+            .collect();
+        let mut keep = mapping.iter();
+        self.strokes.retain(|_| keep.next().unwrap().is_some());
+        self.remap_groups(&mapping);
+    }
+
+    /// Splits the stroke at `index` before point `at` and inserts the tail after it, see `Stroke::split_off`
+    ///
+    /// References to the stroke are divided between both parts according to their point ranges.
+    pub fn split_stroke(&mut self, index: usize, at: usize) {
+        let tail = self.strokes[index].split_off(at);
+        self.strokes.insert(index + 1, tail);
+        self.remap_references(|r| match (r.stroke, r.points) {
+            (stroke, _) if stroke < index => vec![r.clone()],
+            (stroke, _) if stroke > index => vec![StrokeReference { stroke: stroke + 1, points: r.points }],
+            (_, None) => vec![r.clone(), StrokeReference { stroke: index + 1, points: None }],
+            (_, Some((start, end))) => {
+                let mut parts = vec![];
+                if start < at || end <= at {
+                    parts.push(StrokeReference { stroke: index, points: Some((start, end.min(at))) });
+                }
+                if end > at {
+                    parts.push(StrokeReference { stroke: index + 1, points: Some((start.max(at) - at, end - at)) });
+                }
+                parts
+            }
+        });
+    }
+
+    fn remap_groups(&mut self, mapping: &[Option<usize>]) {
+        for group in self.groups.iter_mut() {
+            group.remap_strokes(mapping);
+        }
+    }
+
+    fn remap_references<F>(&mut self, mut f: F) where F: FnMut(&StrokeReference) -> Vec<StrokeReference> {
+        for group in self.groups.iter_mut() {
+            group.remap_references(&mut f);
+        }
+    }
+
+    /// Shifts point ranges onto the points left after removing points, `keep[i]` tells which points of stroke `i` remain
+    ///
+    /// Ranges that lose all their points are widened to the whole stroke.
+    fn remap_kept_points(&mut self, keep: &[Vec<bool>]) {
+        self.remap_references(|r| {
+            let points = match (r.points, keep.get(r.stroke)) {
+                (Some((start, end)), Some(keep)) => {
+                    let kept_before = |i: usize| keep[..i.min(keep.len())].iter().filter(|&&k| k).count();
+                    Some((kept_before(start), kept_before(end))).filter(|(s, e)| s < e || start == end)
+                }
+                (points, _) => points,
+            };
+            vec![StrokeReference { stroke: r.stroke, points }]
+        });
+    }
+
+    /// Widens point ranges to whole strokes after the points of the strokes were replaced
+    fn clear_point_ranges(&mut self) {
+        self.remap_references(|r| vec![StrokeReference { stroke: r.stroke, points: None }]);
+    }
+
+    /// Iterates over the points of all strokes together with the index of their stroke
+    pub fn points(&self) -> impl Iterator<Item = (usize, Point)> + '_ {
+        self.strokes.iter().enumerate().flat_map(|(i, stroke)| stroke.points().map(move |p| (i, p)))
+    }
+
     pub fn x_min(&self) -> f64 {
-        let mut x_min: f64 = std::f64::MAX;
+        let mut x_min: f64 = f64::MAX;
 
         for stroke in self.strokes.iter() {
             let stroke_min = stroke.x_min();
@@ -43,7 +161,7 @@ impl Sketch {
     }
 
     pub fn x_max(&self) -> f64 {
-        let mut x_max: f64 = std::f64::MIN;
+        let mut x_max: f64 = f64::MIN;
 
         for stroke in self.strokes.iter() {
             let stroke_max = stroke.x_max();
@@ -56,7 +174,7 @@ impl Sketch {
     }
 
     pub fn y_min(&self) -> f64 {
-        let mut y_min: f64 = std::f64::MAX;
+        let mut y_min: f64 = f64::MAX;
 
         for stroke in self.strokes.iter() {
             let stroke_min = stroke.y_min();
@@ -69,7 +187,7 @@ impl Sketch {
     }
 
     pub fn y_max(&self) -> f64 {
-        let mut y_max: f64 = std::f64::MIN;
+        let mut y_max: f64 = f64::MIN;
 
         for stroke in self.strokes.iter() {
             let stroke_max = stroke.y_max();
@@ -82,7 +200,7 @@ impl Sketch {
     }
 
     pub fn timestamp_min(&self) -> u64 {
-        let mut timestamp_min: u64 = std::u64::MAX;
+        let mut timestamp_min: u64 = u64::MAX;
 
         for stroke in self.strokes.iter() {
             let stroke_min = stroke.timestamp_min();
@@ -95,7 +213,7 @@ impl Sketch {
     }
 
     pub fn timestamp_max(&self) -> u64 {
-        let mut timestamp_max: u64 = std::u64::MIN;
+        let mut timestamp_max: u64 = u64::MIN;
 
         for stroke in self.strokes.iter() {
             let stroke_max = stroke.timestamp_max();
@@ -108,7 +226,7 @@ impl Sketch {
     }
 
     pub fn pressure_min(&self) -> f64 {
-        let mut pressure_min: f64 = std::f64::MAX;
+        let mut pressure_min: f64 = f64::MAX;
 
         for stroke in self.strokes.iter() {
             let stroke_min = stroke.pressure_min();
@@ -121,7 +239,7 @@ impl Sketch {
     }
 
     pub fn pressure_max(&self) -> f64 {
-        let mut pressure_max: f64 = std::f64::MIN;
+        let mut pressure_max: f64 = f64::MIN;
 
         for stroke in self.strokes.iter() {
             let stroke_max = stroke.pressure_max();
@@ -137,6 +255,10 @@ impl Sketch {
         self.strokes.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.strokes.is_empty()
+    }
+
     /// Offset the x/y coordinates by a given offset
     pub fn offset(&mut self, x_offset: Option<f64>, y_offset: Option<f64>) {
         for stroke in self.strokes.iter_mut() {
@@ -151,6 +273,71 @@ impl Sketch {
         }
     }
 
+    /// Applies an affine transform to the x/y coordinates of all strokes
+    pub fn apply_transform(&mut self, transform: &AffineTransform) {
+        for stroke in self.strokes.iter_mut() {
+            stroke.apply_transform(transform);
+        }
+    }
+
+    /// Rotate all strokes by `angle` radians about a pivot, defaulting to the centroid of the sketch
+    pub fn rotate(&mut self, angle: f64, pivot: Option<(f64, f64)>) {
+        let (x, y) = pivot.unwrap_or_else(|| self.centroid());
+        self.apply_transform(&AffineTransform::rotation_about(angle, x, y));
+    }
+
+    /// Mean of the x/y coordinates of all points
+    pub fn centroid(&self) -> (f64, f64) {
+        let count: usize = self.strokes.iter().map(|s| s.len()).sum();
+        if count == 0 {
+            return (0., 0.);
+        }
+
+        let x: f64 = self.strokes.iter().map(|s| s.x.iter().sum::<f64>()).sum();
+        let y: f64 = self.strokes.iter().map(|s| s.y.iter().sum::<f64>()).sum();
+        (x / count as f64, y / count as f64)
+    }
+
+    /// Converts coordinates, timestamps and stroke widths into `target` and adopts it as coordinate system
+    ///
+    /// Fails and leaves the sketch unchanged if the systems cannot be related, see `CoordinateSystem::transform_to`,
+    /// or if a coarser time unit would round distinct timestamps of a stroke onto the same value.
+    pub fn convert_coordinate_system(&mut self, target: &CoordinateSystem) -> Result<(), InkError> {
+        let transform = self.coordinate_system.transform_to(target).ok_or_else(|| {
+            let unrelated = if self.coordinate_system.millimeters_per_unit().is_none() { &self.coordinate_system } else { target };
+            InkError::Conversion(format!("{:?} units without a valid resolution have no physical size", unrelated.unit))
+        })?;
+        let timestamp_factor = self.coordinate_system.timestamp_factor_to(target);
+        let width_factor = transform.a.abs();
+        let convert = |t: u64| (t as f64 * timestamp_factor).round() as u64;
+        if timestamp_factor < 1. {
+            for (i, stroke) in self.strokes.iter().enumerate() {
+                if let Some(j) = stroke.timestamp.windows(2).position(|w| w[0] != w[1] && convert(w[0]) == convert(w[1])) {
+                    return Err(InkError::Conversion(format!(
+                        "timestamps {} and {} of stroke {} fall onto the same {:?}",
+                        j, j + 1, i, target.time_unit)));
+                }
+            }
+        }
+
+        self.apply_transform(&transform);
+        for stroke in self.strokes.iter_mut() {
+            if timestamp_factor != 1. {
+                for t in stroke.timestamp.iter_mut() {
+                    *t = convert(*t);
+                }
+            }
+            if let Some(width) = stroke.style.width.as_mut() {
+                *width *= width_factor;
+            }
+        }
+        if let Some(width) = self.style.width.as_mut() {
+            *width *= width_factor;
+        }
+        self.coordinate_system = *target;
+        Ok(())
+    }
+
     /// Normalize the stroke into a maximum dimension
     pub fn normalize(&mut self, new_size: f64, keep_aspect_ratio: bool) {
         self.offset(Some(- self.x_min()), Some(-self.y_min()));
@@ -166,14 +353,83 @@ impl Sketch {
         }
     }
 
+    /// Validates all strokes, issues are reported together with the index of the stroke
+    pub fn validate(&self) -> Result<(), Vec<(usize, ValidationIssue)>> {
+        let mut issues = vec![];
+        for (i, stroke) in self.strokes.iter().enumerate() {
+            if let Err(stroke_issues) = stroke.validate() {
+                issues.extend(stroke_issues.into_iter().map(|issue| (i, issue)));
+            }
+        }
+
+        if issues.is_empty() { Ok(()) } else { Err(issues) }
+    }
+
+    /// Removes successive dots with same coordinates from every stroke, point ranges of groups are shifted accordingly
     pub fn remove_duplicate_dots(&mut self) {
+        let keep: Vec<Vec<bool>> = self.strokes.iter_mut().map(|s| s.remove_duplicate_dots_keeping()).collect();
+        self.remap_kept_points(&keep);
+    }
+
+    /// Resamples every stroke to points spaced `step` apart along the polyline, point ranges of groups are
+    /// widened to whole strokes
+    pub fn resample_by_distance(&mut self, step: f64) {
+        for stroke in self.strokes.iter_mut() {
+            stroke.resample_by_distance(step);
+        }
+        self.clear_point_ranges();
+    }
+
+    /// Resamples every stroke to `count` points evenly spaced along the polyline, point ranges of groups are
+    /// widened to whole strokes
+    pub fn resample_to_count(&mut self, count: usize) {
         for stroke in self.strokes.iter_mut() {
-            stroke.remove_duplicate_dots();
+            stroke.resample_to_count(count);
+        }
+        self.clear_point_ranges();
+    }
+
+    /// Resamples every stroke to points `interval` timestamp units apart, see `Stroke::resample_by_interval`,
+    /// point ranges of groups are widened to whole strokes
+    pub fn resample_by_interval(&mut self, interval: f64) {
+        for stroke in self.strokes.iter_mut() {
+            stroke.resample_by_interval(interval);
+        }
+        self.clear_point_ranges();
+    }
+
+    /// Resamples every stroke to a fixed sampling rate in Hz, timestamps are in the time unit of the coordinate system,
+    /// point ranges of groups are widened to whole strokes
+    pub fn resample_by_frequency(&mut self, frequency: f64) {
+        let time_unit = self.coordinate_system.time_unit;
+        for stroke in self.strokes.iter_mut() {
+            stroke.resample_by_frequency_with_time_unit(frequency, time_unit);
+        }
+        self.clear_point_ranges();
+    }
+
+    /// Simplifies every stroke and returns the compression ratio, i.e. points before divided by points after
+    ///
+    /// Point ranges of groups are shifted onto the remaining points.
+    pub fn simplify(&mut self, tolerance: f64, algorithm: SimplificationAlgorithm) -> f64 {
+        let points_before: usize = self.strokes.iter().map(|s| s.len()).sum();
+        let keep: Vec<Vec<bool>> = self.strokes.iter_mut().map(|s| s.simplify_keeping(tolerance, algorithm)).collect();
+        self.remap_kept_points(&keep);
+        let points_after: usize = self.strokes.iter().map(|s| s.len()).sum();
+
+        if points_after == 0 { 1. } else { points_before as f64 / points_after as f64 }
+    }
+
+    /// Smooths every stroke with the configured filters, timestamps are in the time unit of the coordinate system
+    pub fn smooth(&mut self, options: &SmoothingOptions) {
+        let time_unit = self.coordinate_system.time_unit;
+        for stroke in self.strokes.iter_mut() {
+            stroke.smooth_with_time_unit(options, time_unit);
         }
     }
 
     pub fn remove_single_dot_strokes(&mut self) {
-        self.strokes.retain(|stroke| stroke.len() > 1);
+        self.retain_strokes(|stroke| stroke.len() > 1);
     }
 }
 
@@ -187,7 +443,10 @@ impl PartialEq for Sketch {
     fn eq(&self, other: &Self) -> bool {
         self.typ == other.typ &&
             self.meta == other.meta &&
-            self.strokes == other.strokes
+            self.strokes == other.strokes &&
+            self.groups == other.groups &&
+            self.style == other.style &&
+            self.coordinate_system == other.coordinate_system
     }
 }
 
@@ -199,7 +458,7 @@ impl<'de> Deserialize<'de> for Sketch {
         where
             D: Deserializer<'de>,
     {
-        enum Field { Type, Meta, Strokes };
+        enum Field { Type, Meta, Strokes, Groups, Style, CoordinateSystem }
 
         // This part could also be generated independently by:
         //
@@ -217,7 +476,7 @@ impl<'de> Deserialize<'de> for Sketch {
                     type Value = Field;
 
                     fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-                        formatter.write_str("`type` or `meta` or `strokes`")
+                        formatter.write_str("`type`, `meta`, `strokes`, `groups`, `style` or `coordinate_system`")
                     }
 
                     fn visit_str<E>(self, value: &str) -> Result<Field, E>
@@ -228,6 +487,9 @@ impl<'de> Deserialize<'de> for Sketch {
                             "type" => Ok(Field::Type),
                             "meta" => Ok(Field::Meta),
                             "strokes" => Ok(Field::Strokes),
+                            "groups" => Ok(Field::Groups),
+                            "style" => Ok(Field::Style),
+                            "coordinate_system" => Ok(Field::CoordinateSystem),
                             _ => Err(de::Error::unknown_field(value, FIELDS)),
                         }
                     }
@@ -256,9 +518,15 @@ impl<'de> Deserialize<'de> for Sketch {
                     .ok_or_else(|| de::Error::invalid_length(1, &self))?;
                 let strokes = seq.next_element()?
                     .ok_or_else(|| de::Error::invalid_length(2, &self))?;
+                let groups = seq.next_element()?.unwrap_or_default();
+                let style = seq.next_element()?.unwrap_or_default();
+                let coordinate_system = seq.next_element()?.unwrap_or_default();
                 let mut sketch = Sketch::new(strokes);
                 sketch.typ = typ;
                 sketch.meta = meta;
+                sketch.groups = groups;
+                sketch.style = style;
+                sketch.coordinate_system = coordinate_system;
                 Ok(sketch)
             }
 
@@ -269,6 +537,9 @@ impl<'de> Deserialize<'de> for Sketch {
                 let mut typ = None;
                 let mut meta = None;
                 let mut strokes = None;
+                let mut groups = None;
+                let mut style = None;
+                let mut coordinate_system = None;
                 while let Some(key) = map.next_key()? {
                     match key {
                         Field::Type => {
@@ -289,6 +560,24 @@ impl<'de> Deserialize<'de> for Sketch {
                             }
                             strokes = Some(map.next_value()?);
                         }
+                        Field::Groups => {
+                            if groups.is_some() {
+                                return Err(de::Error::duplicate_field("groups"));
+                            }
+                            groups = Some(map.next_value()?);
+                        }
+                        Field::Style => {
+                            if style.is_some() {
+                                return Err(de::Error::duplicate_field("style"));
+                            }
+                            style = Some(map.next_value()?);
+                        }
+                        Field::CoordinateSystem => {
+                            if coordinate_system.is_some() {
+                                return Err(de::Error::duplicate_field("coordinate_system"));
+                            }
+                            coordinate_system = Some(map.next_value()?);
+                        }
                     }
                 }
                 let typ = typ.ok_or_else(|| de::Error::missing_field("type"))?;
@@ -297,11 +586,14 @@ impl<'de> Deserialize<'de> for Sketch {
                 let mut sketch = Sketch::new(strokes);
                 sketch.meta = meta;
                 sketch.typ = typ;
+                sketch.groups = groups.unwrap_or_default();
+                sketch.style = style.unwrap_or_default();
+                sketch.coordinate_system = coordinate_system.unwrap_or_default();
                 Ok(sketch)
             }
         }
 
-        const FIELDS: &'static [&'static str] = &["type", "meta", "strokes"];
+        const FIELDS: &[&str] = &["type", "meta", "strokes", "groups", "style", "coordinate_system"];
         deserializer.deserialize_struct("Sketch", FIELDS, SketchVisitor)
     }
 }
\ No newline at end of file
@@ -0,0 +1,163 @@
+use std::f64::consts::PI;
+
+use crate::units::TimeUnit;
+
+/// Smoothing filters applicable to the channels of a stroke
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SmoothingFilter {
+    /// Centered mean over `window` points, shrinking at the ends of the stroke
+    MovingAverage { window: usize },
+    /// Convolution with a Gaussian kernel of standard deviation `sigma`, given in points
+    Gaussian { sigma: f64 },
+    /// Local least-squares fit of a polynomial of degree `order` over `window` points
+    SavitzkyGolay { window: usize, order: usize },
+    /// Speed-adaptive low-pass filter by Casiez et al., cutoffs are given in Hz
+    ///
+    /// Time steps are taken from the timestamps, `frequency` is used for strokes without
+    /// timestamps and for points sharing a timestamp.
+    OneEuro { min_cutoff: f64, beta: f64, derivative_cutoff: f64, frequency: f64 },
+}
+
+/// Filters applied by `Stroke::smooth`, channels without filter are left unchanged
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SmoothingOptions {
+    /// Filter applied to `x` and `y`
+    pub position: Option<SmoothingFilter>,
+    /// Filter applied to `pressure`
+    pub pressure: Option<SmoothingFilter>,
+}
+
+impl SmoothingFilter {
+    /// Returns the filtered values, `timestamp` is only used by the one-euro filter and may be empty
+    ///
+    /// Timestamps are taken in milliseconds.
+    pub fn apply(&self, values: &[f64], timestamp: &[u64]) -> Vec<f64> {
+        self.apply_with_time_unit(values, timestamp, TimeUnit::Millisecond)
+    }
+
+    /// Returns the filtered values with timestamps in `time_unit`
+    pub fn apply_with_time_unit(&self, values: &[f64], timestamp: &[u64], time_unit: TimeUnit) -> Vec<f64> {
+        match *self {
+            SmoothingFilter::MovingAverage { window } => moving_average(values, window),
+            SmoothingFilter::Gaussian { sigma } => gaussian(values, sigma),
+            SmoothingFilter::SavitzkyGolay { window, order } => savitzky_golay(values, window, order),
+            SmoothingFilter::OneEuro { min_cutoff, beta, derivative_cutoff, frequency } =>
+                one_euro(values, timestamp, time_unit, min_cutoff, beta, derivative_cutoff, frequency),
+        }
+    }
+}
+
+fn moving_average(values: &[f64], window: usize) -> Vec<f64> {
+    let half = window / 2;
+    (0..values.len())
+        .map(|i| {
+            let start = i.saturating_sub(half);
+            let end = (i + half + 1).min(values.len());
+            values[start..end].iter().sum::<f64>() / (end - start) as f64
+        })
+        .collect()
+}
+
+fn gaussian(values: &[f64], sigma: f64) -> Vec<f64> {
+    if sigma <= 0. {
+        return values.to_vec();
+    }
+
+    let radius = (3. * sigma).ceil() as usize;
+    let kernel: Vec<f64> = (0..=radius).map(|d| (-((d * d) as f64) / (2. * sigma * sigma)).exp()).collect();
+    (0..values.len())
+        .map(|i| {
+            let start = i.saturating_sub(radius);
+            let end = (i + radius + 1).min(values.len());
+            let mut sum = 0.;
+            let mut weight_sum = 0.;
+            for (j, value) in values.iter().enumerate().take(end).skip(start) {
+                let weight = kernel[j.abs_diff(i)];
+                sum += weight * value;
+                weight_sum += weight;
+            }
+            sum / weight_sum
+        })
+        .collect()
+}
+
+fn savitzky_golay(values: &[f64], window: usize, order: usize) -> Vec<f64> {
+    let half = window / 2;
+    (0..values.len())
+        .map(|i| {
+            let start = i.saturating_sub(half);
+            let end = (i + half + 1).min(values.len());
+            let degree = order.min(end - start - 1);
+            fit_polynomial(&values[start..end], (i - start) as f64, degree)
+        })
+        .collect()
+}
+
+/// Fits a polynomial of `degree` through the samples at positions 0, 1, ... and evaluates it at `position`
+fn fit_polynomial(samples: &[f64], position: f64, degree: usize) -> f64 {
+    let size = degree + 1;
+    let mut matrix = vec![vec![0.; size + 1]; size];
+    for (k, &sample) in samples.iter().enumerate() {
+        // Centering the abscissa keeps the normal equations well conditioned
+        let t = k as f64 - position;
+        let powers: Vec<f64> = (0..2 * size).map(|p| t.powi(p as i32)).collect();
+        for row in 0..size {
+            for column in 0..size {
+                matrix[row][column] += powers[row + column];
+            }
+            matrix[row][size] += powers[row] * sample;
+        }
+    }
+
+    // Gaussian elimination with partial pivoting, the constant term is the value at `position`
+    for column in 0..size {
+        let pivot = (column..size)
+            .max_by(|&a, &b| matrix[a][column].abs().partial_cmp(&matrix[b][column].abs()).unwrap_or(std::cmp::Ordering::Equal))
+            .unwrap();
+        matrix.swap(column, pivot);
+        if matrix[column][column] == 0. {
+            continue;
+        }
+        let pivot_row = matrix[column].clone();
+        for (row, values) in matrix.iter_mut().enumerate() {
+            if row != column {
+                let factor = values[column] / pivot_row[column];
+                for (value, pivot_value) in values.iter_mut().zip(pivot_row.iter()).skip(column) {
+                    *value -= factor * pivot_value;
+                }
+            }
+        }
+    }
+
+    matrix[0][size] / matrix[0][0]
+}
+
+fn one_euro(values: &[f64], timestamp: &[u64], time_unit: TimeUnit, min_cutoff: f64, beta: f64, derivative_cutoff: f64, frequency: f64) -> Vec<f64> {
+    let alpha = |cutoff: f64, dt: f64| {
+        let tau = 1. / (2. * PI * cutoff);
+        1. / (1. + tau / dt)
+    };
+
+    let mut filtered = Vec::with_capacity(values.len());
+    let mut derivative = 0.;
+    for i in 0..values.len() {
+        if i == 0 {
+            filtered.push(values[0]);
+            continue;
+        }
+
+        let dt = if timestamp.len() == values.len() && timestamp[i] > timestamp[i - 1] {
+            (timestamp[i] - timestamp[i - 1]) as f64 / time_unit.per_second()
+        } else {
+            1. / frequency
+        };
+        let previous = filtered[i - 1];
+        let a = alpha(derivative_cutoff, dt);
+        derivative = a * (values[i] - previous) / dt + (1. - a) * derivative;
+        let cutoff = min_cutoff + beta * derivative.abs();
+        let a = alpha(cutoff, dt);
+        filtered.push(a * values[i] + (1. - a) * previous);
+    }
+
+    filtered
+}
@@ -1,10 +1,20 @@
 use std::collections::HashMap;
 use std::fmt;
+use std::iter::FromIterator;
 
 use serde::{Deserialize, Serialize};
 use serde::de::{self, Deserializer, MapAccess, SeqAccess, Visitor};
 use serde_json::json;
 
+use crate::channel::Channel;
+use crate::point::{self, Point, PointsMut};
+use crate::simplification::{self, SimplificationAlgorithm};
+use crate::smoothing::SmoothingOptions;
+use crate::style::StrokeStyle;
+use crate::transform::AffineTransform;
+use crate::units::TimeUnit;
+use crate::validation::ValidationIssue;
+
 #[derive(Clone, Debug, Serialize)]
 pub struct Stroke {
     #[serde(rename = "type")]
@@ -14,11 +24,16 @@ pub struct Stroke {
     pub y: Vec<f64>,
     pub timestamp: Vec<u64>,
     pub pressure: Vec<f64>,
+    /// Extra per-point channels, each holding one value per point
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub channels: HashMap<String, Channel>,
+    #[serde(skip_serializing_if = "StrokeStyle::is_empty")]
+    pub style: StrokeStyle,
 }
 
 impl Stroke {
     pub fn new(x: Vec<f64>, y: Vec<f64>, timestamp: Vec<u64>, pressure: Vec<f64>) -> Stroke {
-        Stroke { typ: String::from("stroke"), meta: HashMap::new(), x, y, timestamp, pressure }
+        Stroke { typ: String::from("stroke"), meta: HashMap::new(), x, y, timestamp, pressure, channels: HashMap::new(), style: StrokeStyle::default() }
     }
 
     pub fn x(&self) -> &Vec<f64> {
@@ -37,42 +52,83 @@ impl Stroke {
         &self.pressure
     }
 
+    /// Builds a stroke from points, `timestamp` and `pressure` are only kept if every point provides them
+    pub fn from_points(points: &[Point]) -> Stroke {
+        points.iter().cloned().collect()
+    }
+
+    pub fn point(&self, index: usize) -> Point {
+        Point {
+            x: self.x[index],
+            y: self.y[index],
+            timestamp: self.timestamp.get(index).cloned(),
+            pressure: self.pressure.get(index).cloned(),
+        }
+    }
+
+    pub fn points(&self) -> impl Iterator<Item = Point> + '_ {
+        (0..self.len()).map(move |i| self.point(i))
+    }
+
+    pub fn points_mut(&mut self) -> PointsMut<'_> {
+        let length = self.len();
+        PointsMut {
+            x: self.x.iter_mut(),
+            y: self.y.iter_mut(),
+            timestamp: if self.timestamp.len() == length { Some(self.timestamp.iter_mut()) } else { None },
+            pressure: if self.pressure.len() == length { Some(self.pressure.iter_mut()) } else { None },
+        }
+    }
+
+    pub fn channel(&self, name: &str) -> Option<&Channel> {
+        self.channels.get(name)
+    }
+
+    /// Adds or replaces an extra channel
+    pub fn set_channel(&mut self, name: &str, channel: Channel) {
+        self.channels.insert(String::from(name), channel);
+    }
+
     pub fn x_min(&self) -> f64 {
-        self.x.iter().fold(std::f64::MAX, |a, &b| a.min(b))
+        self.x.iter().fold(f64::MAX, |a, &b| a.min(b))
     }
 
     pub fn x_max(&self) -> f64 {
-        self.x.iter().fold(std::f64::MIN, |a, &b| a.max(b))
+        self.x.iter().fold(f64::MIN, |a, &b| a.max(b))
     }
 
     pub fn y_min(&self) -> f64 {
-        self.y.iter().fold(std::f64::MAX, |a, &b| a.min(b))
+        self.y.iter().fold(f64::MAX, |a, &b| a.min(b))
     }
 
     pub fn y_max(&self) -> f64 {
-        self.y.iter().fold(std::f64::MIN, |a, &b| a.max(b))
+        self.y.iter().fold(f64::MIN, |a, &b| a.max(b))
     }
 
     pub fn timestamp_min(&self) -> u64 {
-        self.timestamp.iter().fold(std::u64::MAX, |a, &b| a.min(b))
+        self.timestamp.iter().fold(u64::MAX, |a, &b| a.min(b))
     }
 
     pub fn timestamp_max(&self) -> u64 {
-        self.timestamp.iter().fold(std::u64::MIN, |a, &b| a.max(b))
+        self.timestamp.iter().fold(u64::MIN, |a, &b| a.max(b))
     }
 
     pub fn pressure_min(&self) -> f64 {
-        self.pressure.iter().fold(std::f64::MAX, |a, &b| a.min(b))
+        self.pressure.iter().fold(f64::MAX, |a, &b| a.min(b))
     }
 
     pub fn pressure_max(&self) -> f64 {
-        self.pressure.iter().fold(std::f64::MIN, |a, &b| a.max(b))
+        self.pressure.iter().fold(f64::MIN, |a, &b| a.max(b))
     }
 
     pub fn len(&self) -> usize {
         self.x.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.x.is_empty()
+    }
+
     /// Offset the x/y coordinates by a given offset
     pub fn offset(&mut self, x_offset: Option<f64>, y_offset: Option<f64>) {
         let x_offset = x_offset.unwrap_or(0.);
@@ -109,21 +165,353 @@ impl Stroke {
         }
     }
 
+    /// Applies an affine transform to the x/y coordinates
+    pub fn apply_transform(&mut self, transform: &AffineTransform) {
+        for i in 0..self.x.len().min(self.y.len()) {
+            let (x, y) = transform.apply(self.x[i], self.y[i]);
+            self.x[i] = x;
+            self.y[i] = y;
+        }
+    }
+
+    /// Rotate the x/y coordinates by `angle` radians about a pivot, defaulting to the centroid
+    pub fn rotate(&mut self, angle: f64, pivot: Option<(f64, f64)>) {
+        let (x, y) = pivot.unwrap_or_else(|| self.centroid());
+        self.apply_transform(&AffineTransform::rotation_about(angle, x, y));
+    }
+
+    /// Mean of the x/y coordinates
+    pub fn centroid(&self) -> (f64, f64) {
+        if self.is_empty() {
+            return (0., 0.);
+        }
+        (self.x.iter().sum::<f64>() / self.x.len() as f64, self.y.iter().sum::<f64>() / self.y.len() as f64)
+    }
+
+    /// Checks channel lengths, finiteness of the values and ordering of the timestamps
+    pub fn validate(&self) -> Result<(), Vec<ValidationIssue>> {
+        let mut issues = vec![];
+        if self.is_empty() {
+            issues.push(ValidationIssue::EmptyStroke);
+        }
+
+        let lengths = [("y", self.y.len()), ("timestamp", self.timestamp.len()), ("pressure", self.pressure.len())];
+        for &(channel, length) in lengths.iter() {
+            let optional = channel == "timestamp" || channel == "pressure";
+            if length != self.len() && !(optional && length == 0) {
+                issues.push(ValidationIssue::LengthMismatch { channel: String::from(channel), expected: self.len(), found: length });
+            }
+        }
+
+        for &(channel, values) in [("x", &self.x), ("y", &self.y), ("pressure", &self.pressure)].iter() {
+            if let Some(index) = values.iter().position(|v| !v.is_finite()) {
+                issues.push(ValidationIssue::NonFiniteValue { channel: String::from(channel), index });
+            }
+        }
+
+        let mut names: Vec<&String> = self.channels.keys().collect();
+        names.sort();
+        for name in names {
+            let channel = &self.channels[name];
+            if channel.len() != self.len() {
+                issues.push(ValidationIssue::LengthMismatch { channel: name.clone(), expected: self.len(), found: channel.len() });
+            }
+            if let Channel::Float(values) = channel {
+                if let Some(index) = values.iter().position(|v| !v.is_finite()) {
+                    issues.push(ValidationIssue::NonFiniteValue { channel: name.clone(), index });
+                }
+            }
+        }
+
+        if let Some(index) = (1..self.timestamp.len()).find(|&i| self.timestamp[i] < self.timestamp[i - 1]) {
+            issues.push(ValidationIssue::NonMonotonicTimestamp { index });
+        }
+
+        if issues.is_empty() { Ok(()) } else { Err(issues) }
+    }
+
     /// Removes successive dots with same coordinates
     pub fn remove_duplicate_dots(&mut self) {
-        for i in (1..self.x.len()).rev() {
+        self.remove_duplicate_dots_keeping();
+    }
+
+    /// Removes successive dots with same coordinates, returning for every former point whether it was kept
+    pub(crate) fn remove_duplicate_dots_keeping(&mut self) -> Vec<bool> {
+        let mut keep = vec![true; self.x.len()];
+        for i in (1..self.x.len().min(self.y.len())).rev() {
             let current_x = self.x[i];
             let current_y = self.y[i];
             let previous_x = self.x[i - 1];
             let previous_y = self.y[i - 1];
 
             if current_x == previous_x && current_y == previous_y {
-                self.x.swap_remove(i);
-                self.y.swap_remove(i);
-                self.timestamp.swap_remove(i);
-                self.pressure.swap_remove(i);
+                keep[i] = false;
+                self.x.remove(i);
+                self.y.remove(i);
+                if i < self.timestamp.len() {
+                    self.timestamp.remove(i);
+                }
+                if i < self.pressure.len() {
+                    self.pressure.remove(i);
+                }
+                for channel in self.channels.values_mut() {
+                    if i < channel.len() {
+                        channel.remove(i);
+                    }
+                }
             }
         }
+        keep
+    }
+
+    /// Splits the stroke at `at`, keeping the points before and returning the points from `at` on
+    pub fn split_off(&mut self, at: usize) -> Stroke {
+        if at > self.len() { panic!("split index ({}) should not exceed the length ({})", at, self.len()); }
+
+        let mut tail = Stroke::new(
+            self.x.split_off(at),
+            self.y.split_off(at.min(self.y.len())),
+            self.timestamp.split_off(at.min(self.timestamp.len())),
+            self.pressure.split_off(at.min(self.pressure.len())),
+        );
+        tail.typ = self.typ.clone();
+        tail.meta = self.meta.clone();
+        tail.style = self.style.clone();
+        for (name, channel) in self.channels.iter_mut() {
+            let at = at.min(channel.len());
+            tail.channels.insert(name.clone(), channel.split_off(at));
+        }
+        tail
+    }
+
+    /// Drops points that do not contribute to the shape, keeping their timestamp and pressure aligned
+    pub fn simplify(&mut self, tolerance: f64, algorithm: SimplificationAlgorithm) {
+        self.simplify_keeping(tolerance, algorithm);
+    }
+
+    /// Simplifies the stroke, returning for every former point whether it was kept
+    pub(crate) fn simplify_keeping(&mut self, tolerance: f64, algorithm: SimplificationAlgorithm) -> Vec<bool> {
+        let keep = simplification::simplify(&self.x, &self.y, tolerance, algorithm);
+        self.retain_points(&keep);
+        keep
+    }
+
+    /// Applies the configured filters to the position and pressure channels, assuming timestamps in milliseconds
+    pub fn smooth(&mut self, options: &SmoothingOptions) {
+        self.smooth_with_time_unit(options, TimeUnit::Millisecond);
+    }
+
+    /// Applies the configured filters to the position and pressure channels with timestamps in `time_unit`
+    pub fn smooth_with_time_unit(&mut self, options: &SmoothingOptions, time_unit: TimeUnit) {
+        if let Some(filter) = options.position {
+            self.x = filter.apply_with_time_unit(&self.x, &self.timestamp, time_unit);
+            self.y = filter.apply_with_time_unit(&self.y, &self.timestamp, time_unit);
+        }
+        if let Some(filter) = options.pressure {
+            self.pressure = filter.apply_with_time_unit(&self.pressure, &self.timestamp, time_unit);
+        }
+    }
+
+    /// Keeps the points whose entry in `keep` is true
+    fn retain_points(&mut self, keep: &[bool]) {
+        fn retain<T: Copy>(values: &mut Vec<T>, keep: &[bool]) {
+            if values.len() == keep.len() {
+                *values = values.iter().zip(keep.iter()).filter(|(_, &k)| k).map(|(&v, _)| v).collect();
+            }
+        }
+
+        retain(&mut self.x, keep);
+        retain(&mut self.y, keep);
+        retain(&mut self.timestamp, keep);
+        retain(&mut self.pressure, keep);
+
+        let indices: Vec<usize> = (0..keep.len()).filter(|&i| keep[i]).collect();
+        for channel in self.channels.values_mut() {
+            if channel.len() == keep.len() {
+                *channel = channel.select(&indices);
+            }
+        }
+    }
+
+    /// Length of the polyline through all points
+    pub fn arc_length(&self) -> f64 {
+        self.cumulative_arc_length().last().cloned().unwrap_or(0.)
+    }
+
+    /// Resamples the stroke to points spaced `step` apart along the polyline, keeping first and last point
+    pub fn resample_by_distance(&mut self, step: f64) {
+        if step <= 0. { panic!("step ({}) should be greater than zero", step); }
+        if self.len() < 2 {
+            return;
+        }
+
+        let cumulative = self.cumulative_arc_length();
+        let total = cumulative[cumulative.len() - 1];
+        let mut targets = vec![];
+        let mut k = 0;
+        while (k as f64) * step < total {
+            targets.push(k as f64 * step);
+            k += 1;
+        }
+        targets.push(total);
+
+        self.resample_at(&cumulative, &targets);
+    }
+
+    /// Resamples the stroke to `count` points evenly spaced along the polyline, keeping first and last point
+    pub fn resample_to_count(&mut self, count: usize) {
+        if self.is_empty() {
+            return;
+        }
+
+        let cumulative = self.cumulative_arc_length();
+        let total = cumulative[cumulative.len() - 1];
+        let targets: Vec<f64> = match count {
+            0 => vec![],
+            1 => vec![0.],
+            _ => (0..count).map(|k| total * k as f64 / (count - 1) as f64).collect(),
+        };
+
+        self.resample_at(&cumulative, &targets);
+    }
+
+    /// Resamples the stroke to points `interval` timestamp units apart, keeping first and last point
+    ///
+    /// Points are ordered by timestamp first and points sharing a timestamp are averaged. Strokes without
+    /// timestamps are left unchanged.
+    pub fn resample_by_interval(&mut self, interval: f64) {
+        if interval <= 0. { panic!("interval ({}) should be greater than zero", interval); }
+        if self.is_empty() || self.timestamp.len() != self.len() {
+            return;
+        }
+
+        self.sort_by_timestamp();
+        self.merge_duplicate_timestamps();
+
+        let keys: Vec<f64> = self.timestamp.iter().map(|&t| t as f64).collect();
+        let start = keys[0];
+        let end = keys[keys.len() - 1];
+        let mut targets = vec![];
+        let mut k = 0;
+        while start + k as f64 * interval < end {
+            targets.push(start + k as f64 * interval);
+            k += 1;
+        }
+        targets.push(end);
+
+        self.resample_at(&keys, &targets);
+    }
+
+    /// Resamples the stroke to a fixed sampling rate in Hz, assuming timestamps in milliseconds
+    pub fn resample_by_frequency(&mut self, frequency: f64) {
+        self.resample_by_frequency_with_time_unit(frequency, TimeUnit::Millisecond);
+    }
+
+    /// Resamples the stroke to a fixed sampling rate in Hz with timestamps in `time_unit`
+    pub fn resample_by_frequency_with_time_unit(&mut self, frequency: f64, time_unit: TimeUnit) {
+        if frequency <= 0. { panic!("frequency ({}) should be greater than zero", frequency); }
+        self.resample_by_interval(time_unit.per_second() / frequency);
+    }
+
+    /// Stable sort of all points by their timestamp
+    fn sort_by_timestamp(&mut self) {
+        if self.timestamp.windows(2).all(|w| w[0] <= w[1]) {
+            return;
+        }
+
+        let mut order: Vec<usize> = (0..self.len()).collect();
+        order.sort_by_key(|&i| self.timestamp[i]);
+        self.x = order.iter().map(|&i| self.x[i]).collect();
+        self.y = order.iter().map(|&i| self.y[i]).collect();
+        self.timestamp = order.iter().map(|&i| self.timestamp[i]).collect();
+        if self.pressure.len() == order.len() {
+            self.pressure = order.iter().map(|&i| self.pressure[i]).collect();
+        }
+        for channel in self.channels.values_mut() {
+            if channel.len() == order.len() {
+                *channel = channel.select(&order);
+            }
+        }
+    }
+
+    /// Replaces runs of points with the same timestamp by their average
+    fn merge_duplicate_timestamps(&mut self) {
+        let mut runs = vec![];
+        let mut start = 0;
+        while start < self.len() {
+            let mut end = start + 1;
+            while end < self.len() && self.timestamp[end] == self.timestamp[start] {
+                end += 1;
+            }
+            runs.push((start, end));
+            start = end;
+        }
+        if runs.len() == self.len() {
+            return;
+        }
+
+        let average = |values: &[f64]| runs.iter()
+            .map(|&(start, end)| values[start..end].iter().sum::<f64>() / (end - start) as f64)
+            .collect::<Vec<f64>>();
+        self.x = average(&self.x);
+        self.y = average(&self.y);
+        if self.pressure.len() == self.timestamp.len() {
+            self.pressure = average(&self.pressure);
+        }
+        for channel in self.channels.values_mut() {
+            if channel.len() == self.timestamp.len() {
+                *channel = channel.merge(&runs);
+            }
+        }
+        self.timestamp = runs.iter().map(|&(start, _)| self.timestamp[start]).collect();
+    }
+
+    fn cumulative_arc_length(&self) -> Vec<f64> {
+        let mut cumulative = Vec::with_capacity(self.len());
+        let mut length = 0.;
+        for i in 0..self.len() {
+            if i > 0 {
+                length += ((self.x[i] - self.x[i - 1]).powi(2) + (self.y[i] - self.y[i - 1]).powi(2)).sqrt();
+            }
+            cumulative.push(length);
+        }
+        cumulative
+    }
+
+    /// Replaces all channels by their linear interpolation at `targets`
+    ///
+    /// `keys` holds one non-decreasing value per point, e.g. the arc length, and `targets` must be sorted.
+    fn resample_at(&mut self, keys: &[f64], targets: &[f64]) {
+        let mut segments = Vec::with_capacity(targets.len());
+        let mut j = 0;
+        for &target in targets.iter() {
+            while j + 2 < keys.len() && keys[j + 1] < target {
+                j += 1;
+            }
+            let (i0, i1) = if keys.len() > 1 { (j, j + 1) } else { (0, 0) };
+            let span = keys[i1] - keys[i0];
+            let t = if span > 0. { ((target - keys[i0]) / span).clamp(0., 1.) } else if target >= keys[i1] { 1. } else { 0. };
+            segments.push((i0, i1, t));
+        }
+
+        let lerp = |values: &[f64]| segments.iter()
+            .map(|&(i0, i1, t)| values[i0] + (values[i1] - values[i0]) * t)
+            .collect::<Vec<f64>>();
+        let length = self.len();
+        if self.timestamp.len() == length {
+            let timestamp: Vec<f64> = self.timestamp.iter().map(|&t| t as f64).collect();
+            self.timestamp = lerp(&timestamp).iter().map(|&t| t.round() as u64).collect();
+        }
+        if self.pressure.len() == length {
+            self.pressure = lerp(&self.pressure);
+        }
+        for channel in self.channels.values_mut() {
+            if channel.len() == length {
+                *channel = channel.interpolate(&segments);
+            }
+        }
+        self.x = lerp(&self.x);
+        self.y = lerp(&self.y);
     }
 }
 
@@ -141,7 +529,9 @@ impl PartialEq for Stroke {
             self.x == other.x &&
             self.y == other.y &&
             self.timestamp == other.timestamp &&
-            self.pressure == other.pressure
+            self.pressure == other.pressure &&
+            self.channels == other.channels &&
+            self.style == other.style
     }
 }
 
@@ -166,13 +556,75 @@ impl StrokeBuilder {
         self.pressure.push(pressure);
     }
 
+    /// Builds the stroke, `timestamp` and `pressure` are dropped unless they hold a value for every point
     pub fn build(self) -> Stroke {
-        Stroke { typ: String::from("stroke"), meta: HashMap::new(), x: self.x, y: self.y, timestamp: self.timestamp, pressure: self.pressure }
+        let len = self.x.len();
+        let timestamp = if self.timestamp.len() == len { self.timestamp } else { vec![] };
+        let pressure = if self.pressure.len() == len { self.pressure } else { vec![] };
+        Stroke::new(self.x, self.y, timestamp, pressure)
     }
 
     pub fn len(&self) -> usize {
         self.x.len()
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.x.is_empty()
+    }
+}
+
+impl Default for StrokeBuilder {
+    fn default() -> Self {
+        StrokeBuilder::new()
+    }
+}
+
+/// Appends points, a value missing from a point repeats the previous value of its channel and extra channels,
+/// which points do not carry, repeat their last value, so that every recorded channel stays aligned
+///
+/// Channels the stroke does not record ignore the values of the points, an empty stroke records `timestamp`
+/// and `pressure` if the first appended point has them.
+impl Extend<Point> for Stroke {
+    fn extend<I: IntoIterator<Item = Point>>(&mut self, iter: I) {
+        for p in iter {
+            let len = self.len();
+            if self.timestamp.len() == len && (len > 0 || p.timestamp.is_some()) {
+                let previous = self.timestamp.last().copied().unwrap_or(0);
+                self.timestamp.push(p.timestamp.unwrap_or(previous));
+            }
+            if self.pressure.len() == len && (len > 0 || p.pressure.is_some()) {
+                let previous = self.pressure.last().copied().unwrap_or(0.);
+                self.pressure.push(p.pressure.unwrap_or(previous));
+            }
+            for channel in self.channels.values_mut().filter(|channel| channel.len() == len) {
+                channel.repeat_last();
+            }
+            self.x.push(p.x);
+            self.y.push(p.y);
+        }
+    }
+}
+
+impl FromIterator<Point> for Stroke {
+    fn from_iter<I: IntoIterator<Item = Point>>(iter: I) -> Self {
+        iter.into_iter().collect::<StrokeBuilder>().build()
+    }
+}
+
+impl Extend<Point> for StrokeBuilder {
+    fn extend<I: IntoIterator<Item = Point>>(&mut self, iter: I) {
+        for p in iter {
+            point::push_point(&mut self.x, &mut self.y, &mut self.timestamp, &mut self.pressure, p);
+        }
+    }
+}
+
+impl FromIterator<Point> for StrokeBuilder {
+    fn from_iter<I: IntoIterator<Item = Point>>(iter: I) -> Self {
+        let mut builder = StrokeBuilder::new();
+        builder.extend(iter);
+        builder
+    }
 }
 
 
@@ -181,7 +633,7 @@ impl<'de> Deserialize<'de> for Stroke {
         where
             D: Deserializer<'de>,
     {
-        enum Field { Type, Meta, X, Y, Timestamp, Pressure };
+        enum Field { Type, Meta, X, Y, Timestamp, Pressure, Channels, Style }
 
         // This part could also be generated independently by:
         //
@@ -199,7 +651,7 @@ impl<'de> Deserialize<'de> for Stroke {
                     type Value = Field;
 
                     fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-                        formatter.write_str("`type` or `meta` or `x` or `y` or `timestamp` or `pressure`")
+                        formatter.write_str("`type` or `meta` or `x` or `y` or `timestamp` or `pressure` or `channels` or `style`")
                     }
 
                     fn visit_str<E>(self, value: &str) -> Result<Field, E>
@@ -213,6 +665,8 @@ impl<'de> Deserialize<'de> for Stroke {
                             "y" => Ok(Field::Y),
                             "timestamp" => Ok(Field::Timestamp),
                             "pressure" => Ok(Field::Pressure),
+                            "channels" => Ok(Field::Channels),
+                            "style" => Ok(Field::Style),
                             _ => Err(de::Error::unknown_field(value, FIELDS)),
                         }
                     }
@@ -247,9 +701,13 @@ impl<'de> Deserialize<'de> for Stroke {
                     .ok_or_else(|| de::Error::invalid_length(4, &self))?;
                 let pressure = seq.next_element()?
                     .ok_or_else(|| de::Error::invalid_length(5, &self))?;
+                let channels = seq.next_element()?.unwrap_or_default();
+                let style = seq.next_element()?.unwrap_or_default();
                 let mut stroke = Stroke::new(x, y, timestamp, pressure);
                 stroke.meta = meta;
                 stroke.typ = typ;
+                stroke.channels = channels;
+                stroke.style = style;
                 Ok(stroke)
             }
 
@@ -263,6 +721,8 @@ impl<'de> Deserialize<'de> for Stroke {
                 let mut y = None;
                 let mut timestamp = None;
                 let mut pressure = None;
+                let mut channels = None;
+                let mut style = None;
                 while let Some(key) = map.next_key()? {
                     match key {
                         Field::Type => {
@@ -301,6 +761,18 @@ impl<'de> Deserialize<'de> for Stroke {
                             }
                             pressure = Some(map.next_value()?);
                         }
+                        Field::Channels => {
+                            if channels.is_some() {
+                                return Err(de::Error::duplicate_field("channels"));
+                            }
+                            channels = Some(map.next_value()?);
+                        }
+                        Field::Style => {
+                            if style.is_some() {
+                                return Err(de::Error::duplicate_field("style"));
+                            }
+                            style = Some(map.next_value()?);
+                        }
                     }
                 }
                 let typ = typ.ok_or_else(|| de::Error::missing_field("type"))?;
@@ -309,14 +781,17 @@ impl<'de> Deserialize<'de> for Stroke {
                 let y = y.ok_or_else(|| de::Error::missing_field("y"))?;
                 let timestamp = timestamp.ok_or_else(|| de::Error::missing_field("timestamp"))?;
                 let pressure = pressure.ok_or_else(|| de::Error::missing_field("pressure"))?;
+                let channels = channels.unwrap_or_default();
                 let mut stroke = Stroke::new(x, y, timestamp, pressure);
                 stroke.meta = meta;
                 stroke.typ = typ;
+                stroke.channels = channels;
+                stroke.style = style.unwrap_or_default();
                 Ok(stroke)
             }
         }
 
-        const FIELDS: &'static [&'static str] = &["type", "meta", "x", "y", "timestamp", "pressure"];
+        const FIELDS: &[&str] = &["type", "meta", "x", "y", "timestamp", "pressure", "channels", "style"];
         deserializer.deserialize_struct("Stroke", FIELDS, StrokeVisitor)
     }
 }
\ No newline at end of file
@@ -0,0 +1,86 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+/// Kind of input tool a stroke was drawn with
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Tool {
+    #[default]
+    Pen,
+    Pencil,
+    Highlighter,
+    Marker,
+    Brush,
+}
+
+/// Visual attributes of a stroke, unset attributes are inherited from the sketch style or the renderer
+///
+/// Colors are CSS color strings, hex colors and a few color names are understood by the raster renderer.
+/// They are taken as loaded without validation and escaped when written into SVG attributes.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct StrokeStyle {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub opacity: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool: Option<Tool>,
+}
+
+impl StrokeStyle {
+    pub fn new(color: &str, width: f64, opacity: f64, tool: Tool) -> StrokeStyle {
+        StrokeStyle { color: Some(String::from(color)), width: Some(width), opacity: Some(opacity), tool: Some(tool) }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.color.is_none() && self.width.is_none() && self.opacity.is_none() && self.tool.is_none()
+    }
+
+    /// Style with the unset attributes taken from `parent`
+    pub fn inherit(&self, parent: &StrokeStyle) -> StrokeStyle {
+        StrokeStyle {
+            color: self.color.clone().or_else(|| parent.color.clone()),
+            width: self.width.or(parent.width),
+            opacity: self.opacity.or(parent.opacity),
+            tool: self.tool.or(parent.tool),
+        }
+    }
+}
+
+impl fmt::Display for StrokeStyle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", json!(self))
+    }
+}
+
+/// Parses `#rgb`, `#rrggbb`, `#rrggbbaa` and basic color names into RGBA
+pub fn parse_color(color: &str) -> Option<[u8; 4]> {
+    let color = color.trim();
+    if let Some(hex) = color.strip_prefix('#') {
+        if !hex.is_ascii() {
+            return None;
+        }
+        let channel = |i: usize, n: usize| u8::from_str_radix(&hex[i * n..(i + 1) * n], 16).ok();
+        return match hex.len() {
+            3 => Some([channel(0, 1)? * 17, channel(1, 1)? * 17, channel(2, 1)? * 17, 255]),
+            6 => Some([channel(0, 2)?, channel(1, 2)?, channel(2, 2)?, 255]),
+            8 => Some([channel(0, 2)?, channel(1, 2)?, channel(2, 2)?, channel(3, 2)?]),
+            _ => None,
+        };
+    }
+
+    match color.to_ascii_lowercase().as_str() {
+        "black" => Some([0, 0, 0, 255]),
+        "white" => Some([255, 255, 255, 255]),
+        "gray" | "grey" => Some([128, 128, 128, 255]),
+        "red" => Some([255, 0, 0, 255]),
+        "green" => Some([0, 128, 0, 255]),
+        "blue" => Some([0, 0, 255, 255]),
+        "yellow" => Some([255, 255, 0, 255]),
+        _ => None,
+    }
+}
@@ -0,0 +1,93 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+/// 2×3 affine matrix mapping `(x, y)` to `(a * x + b * y + c, d * x + e * y + f)`
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AffineTransform {
+    pub a: f64,
+    pub b: f64,
+    pub c: f64,
+    pub d: f64,
+    pub e: f64,
+    pub f: f64,
+}
+
+impl AffineTransform {
+    pub fn new(a: f64, b: f64, c: f64, d: f64, e: f64, f: f64) -> AffineTransform {
+        AffineTransform { a, b, c, d, e, f }
+    }
+
+    pub fn identity() -> AffineTransform {
+        AffineTransform::new(1., 0., 0., 0., 1., 0.)
+    }
+
+    pub fn translation(x_offset: f64, y_offset: f64) -> AffineTransform {
+        AffineTransform::new(1., 0., x_offset, 0., 1., y_offset)
+    }
+
+    pub fn scaling(x_factor: f64, y_factor: f64) -> AffineTransform {
+        AffineTransform::new(x_factor, 0., 0., 0., y_factor, 0.)
+    }
+
+    /// Counter-clockwise rotation about the origin by `angle` radians, in a y-up coordinate system
+    pub fn rotation(angle: f64) -> AffineTransform {
+        let (sin, cos) = angle.sin_cos();
+        AffineTransform::new(cos, -sin, 0., sin, cos, 0.)
+    }
+
+    /// Rotation by `angle` radians about the point `(x, y)`
+    pub fn rotation_about(angle: f64, x: f64, y: f64) -> AffineTransform {
+        AffineTransform::translation(-x, -y)
+            .then(&AffineTransform::rotation(angle))
+            .then(&AffineTransform::translation(x, y))
+    }
+
+    /// Shear adding `x_factor * y` to x and `y_factor * x` to y
+    pub fn shear(x_factor: f64, y_factor: f64) -> AffineTransform {
+        AffineTransform::new(1., x_factor, 0., y_factor, 1., 0.)
+    }
+
+    /// Transform applying `self` first and `other` afterwards
+    pub fn then(&self, other: &AffineTransform) -> AffineTransform {
+        AffineTransform::new(
+            other.a * self.a + other.b * self.d,
+            other.a * self.b + other.b * self.e,
+            other.a * self.c + other.b * self.f + other.c,
+            other.d * self.a + other.e * self.d,
+            other.d * self.b + other.e * self.e,
+            other.d * self.c + other.e * self.f + other.f,
+        )
+    }
+
+    /// Inverse transform, `None` if the matrix is singular
+    pub fn inverse(&self) -> Option<AffineTransform> {
+        let determinant = self.a * self.e - self.b * self.d;
+        if determinant == 0. || !determinant.is_finite() {
+            return None;
+        }
+
+        let a = self.e / determinant;
+        let b = -self.b / determinant;
+        let d = -self.d / determinant;
+        let e = self.a / determinant;
+        Some(AffineTransform::new(a, b, -(a * self.c + b * self.f), d, e, -(d * self.c + e * self.f)))
+    }
+
+    pub fn apply(&self, x: f64, y: f64) -> (f64, f64) {
+        (self.a * x + self.b * y + self.c, self.d * x + self.e * y + self.f)
+    }
+}
+
+impl Default for AffineTransform {
+    fn default() -> Self {
+        AffineTransform::identity()
+    }
+}
+
+impl fmt::Display for AffineTransform {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", json!(self))
+    }
+}
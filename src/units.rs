@@ -0,0 +1,162 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::transform::AffineTransform;
+
+const MILLIMETERS_PER_INCH: f64 = 25.4;
+
+/// Resolution assumed for pixels without an explicit resolution, as in CSS
+pub const DEFAULT_PIXEL_RESOLUTION: f64 = 96.;
+
+/// Length unit of the x/y coordinates, the usual abbreviations such as `mm` or `px` are accepted when loading
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Unit {
+    /// Raw digitizer units, their physical size is only known from the resolution
+    #[default]
+    Device,
+    #[serde(alias = "px")]
+    Pixel,
+    #[serde(alias = "mm")]
+    Millimeter,
+    #[serde(alias = "cm")]
+    Centimeter,
+    #[serde(alias = "in")]
+    Inch,
+    /// Typographic point, 1/72 inch
+    #[serde(alias = "pt")]
+    Point,
+}
+
+impl Unit {
+    /// Physical length of one unit in millimeters, `None` for units that depend on a resolution
+    pub fn millimeters(&self) -> Option<f64> {
+        match self {
+            Unit::Device | Unit::Pixel => None,
+            Unit::Millimeter => Some(1.),
+            Unit::Centimeter => Some(10.),
+            Unit::Inch => Some(MILLIMETERS_PER_INCH),
+            Unit::Point => Some(MILLIMETERS_PER_INCH / 72.),
+        }
+    }
+}
+
+/// Direction in which y coordinates grow
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum YDirection {
+    /// Screen and tablet convention, y grows downwards
+    #[default]
+    Down,
+    /// Mathematical convention, y grows upwards
+    Up,
+}
+
+/// Unit of the timestamps
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeUnit {
+    Second,
+    #[default]
+    Millisecond,
+    Microsecond,
+    Nanosecond,
+}
+
+impl TimeUnit {
+    pub fn per_second(&self) -> f64 {
+        match self {
+            TimeUnit::Second => 1.,
+            TimeUnit::Millisecond => 1e3,
+            TimeUnit::Microsecond => 1e6,
+            TimeUnit::Nanosecond => 1e9,
+        }
+    }
+}
+
+/// Describes how the coordinates and timestamps of a sketch map to physical space and time
+///
+/// The default describes device units of unknown size, y growing downwards and timestamps in milliseconds.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CoordinateSystem {
+    pub unit: Unit,
+    /// Units per inch for device units and pixels, e.g. the DPI of a tablet or screen
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolution: Option<f64>,
+    /// Position of the origin on the page in `unit`, measured right and down from the top left corner
+    pub origin: (f64, f64),
+    pub y_direction: YDirection,
+    pub time_unit: TimeUnit,
+}
+
+impl CoordinateSystem {
+    pub fn new(unit: Unit, resolution: Option<f64>) -> CoordinateSystem {
+        CoordinateSystem { unit, resolution, ..CoordinateSystem::default() }
+    }
+
+    pub fn is_default(&self) -> bool {
+        *self == CoordinateSystem::default()
+    }
+
+    /// Physical length of one unit in millimeters, `None` for device units without a valid resolution
+    ///
+    /// Pixels without a resolution are taken at `DEFAULT_PIXEL_RESOLUTION`.
+    pub fn millimeters_per_unit(&self) -> Option<f64> {
+        if let Some(millimeters) = self.unit.millimeters() {
+            return Some(millimeters);
+        }
+
+        let resolution = match (self.unit, self.resolution) {
+            (_, Some(resolution)) => resolution,
+            (Unit::Pixel, None) => DEFAULT_PIXEL_RESOLUTION,
+            _ => return None,
+        };
+        if resolution > 0. && resolution.is_finite() {
+            Some(MILLIMETERS_PER_INCH / resolution)
+        } else {
+            None
+        }
+    }
+
+    /// Transform from this system into page coordinates with y growing downwards, `scale` being the size of one unit
+    fn page_transform(&self, scale: f64) -> AffineTransform {
+        let y_sign = match self.y_direction {
+            YDirection::Down => 1.,
+            YDirection::Up => -1.,
+        };
+        AffineTransform::translation(self.origin.0, y_sign * self.origin.1)
+            .then(&AffineTransform::scaling(scale, y_sign * scale))
+    }
+
+    /// Transform mapping coordinates of this system to millimeters with the origin at the top left and y growing downwards
+    pub fn millimeter_transform(&self) -> Option<AffineTransform> {
+        Some(self.page_transform(self.millimeters_per_unit()?))
+    }
+
+    /// Transform mapping coordinates of this system into `target`
+    ///
+    /// Systems sharing unit and resolution convert without knowing their physical size, other pairs need
+    /// both sizes and yield `None` otherwise.
+    pub fn transform_to(&self, target: &CoordinateSystem) -> Option<AffineTransform> {
+        let (source_scale, target_scale) = if self.unit == target.unit && self.resolution == target.resolution {
+            (1., 1.)
+        } else {
+            (self.millimeters_per_unit()?, target.millimeters_per_unit()?)
+        };
+        Some(self.page_transform(source_scale).then(&target.page_transform(target_scale).inverse()?))
+    }
+
+    /// Factor converting timestamps of this system into the time unit of `target`
+    pub fn timestamp_factor_to(&self, target: &CoordinateSystem) -> f64 {
+        target.time_unit.per_second() / self.time_unit.per_second()
+    }
+}
+
+impl fmt::Display for CoordinateSystem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", json!(self))
+    }
+}
@@ -0,0 +1,27 @@
+use std::fmt;
+
+/// Problem found while validating a stroke
+///
+/// The `timestamp` and `pressure` channels may be left empty when they were not recorded, they only have to
+/// match the length of `x` when present.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ValidationIssue {
+    EmptyStroke,
+    LengthMismatch { channel: String, expected: usize, found: usize },
+    NonFiniteValue { channel: String, index: usize },
+    NonMonotonicTimestamp { index: usize },
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ValidationIssue::EmptyStroke => write!(f, "stroke has no points"),
+            ValidationIssue::LengthMismatch { channel, expected, found } =>
+                write!(f, "channel `{}` has {} values, expected {}", channel, found, expected),
+            ValidationIssue::NonFiniteValue { channel, index } =>
+                write!(f, "channel `{}` has a non-finite value at index {}", channel, index),
+            ValidationIssue::NonMonotonicTimestamp { index } =>
+                write!(f, "timestamp decreases at index {}", index),
+        }
+    }
+}